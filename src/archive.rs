@@ -1,11 +1,11 @@
-use chrono::{NaiveDate, Local, Datelike};
+use chrono::{NaiveDate, Datelike};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
 use crate::jsonl::ProjectHours;
-use crate::schedule::{get_shift_type, ShiftType};
+use crate::schedule::Schedule;
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct DailySummaryFile {
@@ -94,21 +94,13 @@ fn format_hm(hours: f64) -> String {
     format!("{}:{:02}", h, m)
 }
 
-fn shift_name(shift_type: ShiftType) -> &'static str {
-    match shift_type {
-        ShiftType::Regular => "regular",
-        ShiftType::Afternoon => "afternoon",
-        ShiftType::Weekend => "weekend",
-        ShiftType::SaturdayAfternoon => "saturday_afternoon",
-    }
-}
-
 pub fn archive_overtime(
     daily_hours: &HashMap<NaiveDate, f64>,
     daily_projects: &HashMap<NaiveDate, HashMap<String, ProjectHours>>,
+    schedule: &Schedule,
     debug: bool,
 ) {
-    let today = Local::now().date_naive();
+    let today = schedule.today();
     let mut summary = load_summary();
     summary.version = 2;
     
@@ -131,7 +123,7 @@ pub fn archive_overtime(
             continue;
         }
         
-        let shift_type = get_shift_type(*date);
+        let shift_type = schedule.get_shift_type(*date);
         let projects_entry = daily_projects.get(date).map(|projs| {
             projs
                 .iter()
@@ -150,7 +142,7 @@ pub fn archive_overtime(
         let entry = DayEntry {
             hours: *hours,
             formatted: format_hm(*hours),
-            shift: shift_name(shift_type).to_string(),
+            shift: shift_type.name.clone(),
             processed: true,
             projects: projects_entry,
         };
@@ -189,74 +181,3 @@ pub fn archive_overtime(
         }
     }
 }
-
-pub fn archive_overtime_full(
-    daily_hours: &HashMap<NaiveDate, f64>,
-    daily_projects: &HashMap<NaiveDate, HashMap<String, crate::jsonl::ProjectHours>>,
-    debug: bool,
-) {
-    let today = Local::now().date_naive();
-    let mut summary = DailySummaryFile {
-        version: 2,
-        days: HashMap::new(),
-        months: HashMap::new(),
-    };
-    
-    for (date, hours) in daily_hours {
-        if *date == today {
-            continue;
-        }
-        
-        let date_str = date.format("%Y-%m-%d").to_string();
-        let shift_type = get_shift_type(*date);
-        
-        let projects_entry = daily_projects.get(date).map(|projs| {
-            projs
-                .iter()
-                .map(|(name, hours)| {
-                    (
-                        name.clone(),
-                        ProjectHoursEntry {
-                            weekday_hours: hours.weekday_hours,
-                            weekend_hours: hours.weekend_hours,
-                        },
-                    )
-                })
-                .collect()
-        });
-        
-        let entry = DayEntry {
-            hours: *hours,
-            formatted: format_hm(*hours),
-            shift: shift_name(shift_type).to_string(),
-            processed: true,
-            projects: projects_entry,
-        };
-        
-        summary.days.insert(date_str, entry);
-    }
-    
-    let mut monthly_totals: HashMap<String, f64> = HashMap::new();
-    for (date_str, entry) in &summary.days {
-        if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-            let month_key = format!("{}-{:02}", date.year(), date.month());
-            *monthly_totals.entry(month_key).or_insert(0.0) += entry.hours;
-        }
-    }
-    
-    for (month, total) in monthly_totals {
-        summary.months.insert(
-            month,
-            MonthEntry {
-                total_hours: total,
-                formatted: format_hm(total),
-            },
-        );
-    }
-    
-    if let Err(e) = save_summary(&summary) {
-        eprintln!("[ERROR] Failed to save daily_summary.json: {}", e);
-    } else if debug {
-        eprintln!("[DEBUG] Full sync: saved {} days to daily_summary.json", summary.days.len());
-    }
-}
@@ -0,0 +1,363 @@
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::config::LogSourceConfig;
+use crate::jsonl::{find_log_files, modified_in, parse_timestamp, EventDetails, EventKind, LogFiles, SeenRecords, TimestampRecord};
+use crate::projects::ProjectResolver;
+use crate::sources::{ActivitySource, Scope};
+use crate::timezone::TimeZones;
+
+/// Session key of a log named after its session (`rollout-<time>-<id>`,
+/// `session-<time>-<id>`), which stays the same in synced copies.
+fn file_session(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Path a tool call worked on, from its arguments.
+fn argument_path(args: &Value) -> Option<String> {
+    ["file_path", "absolute_path", "path", "dir_path", "workdir"]
+        .iter()
+        .find_map(|key| args[*key].as_str())
+        .map(str::to_string)
+}
+
+/// Codex CLI rollouts (`~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl`): one
+/// object per line with `timestamp`, `type` and `payload`; the working
+/// directory comes from `session_meta` or `turn_context`.
+pub struct CodexSource {
+    files: LogFiles,
+}
+
+impl CodexSource {
+    pub fn from_config(config: &LogSourceConfig) -> Self {
+        Self { files: LogFiles::from_config(config, &["~/.codex/sessions"]) }
+    }
+}
+
+/// Records of one rollout with an empty project, and the session's cwd.
+fn parse_codex(content: &str) -> (Option<String>, Vec<TimestampRecord>) {
+    let mut cwd: Option<String> = None;
+    let mut model: Option<String> = None;
+    let mut records = Vec::new();
+
+    for line in content.lines() {
+        let Ok(entry) = serde_json::from_str::<Value>(line) else { continue };
+        let Some(timestamp) = entry["timestamp"].as_str().and_then(parse_timestamp) else { continue };
+        let payload = &entry["payload"];
+        if cwd.is_none() {
+            cwd = payload["cwd"].as_str().map(str::to_string);
+        }
+        if let Some(m) = payload["model"].as_str() {
+            model = Some(m.to_string());
+        }
+
+        let mut details = EventDetails::default();
+        let mut path = None;
+        let kind = match (entry["type"].as_str(), payload["type"].as_str()) {
+            // The UI's copy of the response items below.
+            (Some("event_msg"), _) => continue,
+            (Some("response_item"), Some("message")) => match payload["role"].as_str() {
+                // Injected context (`<environment_context>`, instructions) is no prompt.
+                Some("user") if payload["content"][0]["text"].as_str().is_some_and(|t| t.starts_with('<')) => EventKind::Other,
+                Some("user") => EventKind::Prompt,
+                _ => EventKind::Assistant,
+            },
+            (Some("response_item"), Some("function_call" | "custom_tool_call" | "local_shell_call")) => {
+                details.tools.push(payload["name"].as_str().unwrap_or("shell").to_string());
+                path = payload["arguments"]
+                    .as_str()
+                    .and_then(|a| serde_json::from_str::<Value>(a).ok())
+                    .and_then(|args| argument_path(&args));
+                EventKind::Tool
+            }
+            (Some("response_item"), Some(t)) if t.ends_with("_output") => EventKind::Tool,
+            (Some("response_item"), Some("reasoning")) => EventKind::Assistant,
+            _ => EventKind::Other,
+        };
+        if kind != EventKind::Prompt {
+            details.model = model.clone();
+        }
+
+        records.push(TimestampRecord {
+            path,
+            details,
+            ..TimestampRecord::new(timestamp, String::new(), kind)
+        });
+    }
+
+    (cwd, records)
+}
+
+impl ActivitySource for CodexSource {
+    fn name(&self) -> &'static str {
+        "codex"
+    }
+
+    fn records(&self, scope: Scope, projects: &ProjectResolver, debug: bool) -> Vec<TimestampRecord> {
+        let mut records = Vec::new();
+        let mut seen = SeenRecords::default();
+
+        for file in self.files.find("jsonl", scope, debug) {
+            let Ok(content) = fs::read_to_string(&file) else { continue };
+            let (cwd, found) = parse_codex(&content);
+            let project = cwd
+                .map(|c| projects.repo_identity(&c).unwrap_or(c))
+                .unwrap_or_else(|| "unknown".to_string());
+            let session = file_session(&file);
+            seen.next_file();
+            records.extend(
+                found
+                    .into_iter()
+                    .filter(|r| seen.is_new(format!("{}|{}", session, r.timestamp)))
+                    .map(|r| TimestampRecord { project: project.clone(), ..r }),
+            );
+        }
+
+        records
+    }
+}
+
+/// Directories never searched for Aider histories.
+const SKIPPED_DIRS: [&str; 3] = ["node_modules", "target", "venv"];
+
+/// Aider prompt histories (`.aider.input.history` in each directory it ran
+/// in): a `# 2025-01-20 18:00:01.123456` line in local time before every
+/// prompt. The directory holding the file is the project. By default the
+/// home directory is searched, five levels deep, skipping hidden directories.
+/// With no session IDs, copies of a history synced into several roots are
+/// matched by their path below the root.
+pub struct AiderSource {
+    files: LogFiles,
+    zones: TimeZones,
+}
+
+impl AiderSource {
+    pub fn from_config(config: &LogSourceConfig, zones: TimeZones) -> Self {
+        Self { files: LogFiles::from_config(config, &["~"]), zones }
+    }
+
+    /// History files modified within `scope`: a history not written to
+    /// since the cutoff holds no prompts after it.
+    fn history_files(&self, scope: Scope) -> Vec<PathBuf> {
+        let searched = |e: &walkdir::DirEntry| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() == 0 || e.file_type().is_file() || !(name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()))
+        };
+        self.files
+            .dirs
+            .iter()
+            .flat_map(|dir| WalkDir::new(dir).max_depth(5).into_iter().filter_entry(searched))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && e.file_name() == ".aider.input.history")
+            .map(|e| e.into_path())
+            .filter(|path| self.files.is_wanted(path) && modified_in(path, scope))
+            .collect()
+    }
+}
+
+/// UTC times of the prompts in an Aider input history, which records
+/// wall-clock times.
+fn parse_aider(content: &str, zones: &TimeZones) -> Vec<NaiveDateTime> {
+    content
+        .lines()
+        .filter_map(|line| line.strip_prefix("# "))
+        .filter_map(|stamp| NaiveDateTime::parse_from_str(stamp.trim(), "%Y-%m-%d %H:%M:%S%.f").ok())
+        .map(|local| zones.to_utc(local))
+        .collect()
+}
+
+impl ActivitySource for AiderSource {
+    fn name(&self) -> &'static str {
+        "aider"
+    }
+
+    fn records(&self, scope: Scope, projects: &ProjectResolver, debug: bool) -> Vec<TimestampRecord> {
+        let mut records = Vec::new();
+        let mut seen = SeenRecords::default();
+
+        for file in self.history_files(scope) {
+            let Ok(content) = fs::read_to_string(&file) else { continue };
+            let dir = file.parent().unwrap_or(Path::new("/")).to_string_lossy().to_string();
+            let project = projects.repo_identity(&dir).unwrap_or(dir);
+            let relative = self.files.relative(&file);
+            seen.next_file();
+            let prompts: Vec<_> = parse_aider(&content, &self.zones)
+                .into_iter()
+                .filter(|t| scope.includes(*t) && seen.is_new(format!("{}|{}", relative.display(), t)))
+                .collect();
+            if debug {
+                eprintln!("[DEBUG] {} Aider prompts in {}", prompts.len(), file.display());
+            }
+            records.extend(prompts.into_iter().map(|t| TimestampRecord::new(t, project.clone(), EventKind::Prompt)));
+        }
+
+        records
+    }
+}
+
+/// Gemini CLI logs (`~/.gemini/tmp/<project hash>/`): `chats/session-*.json`
+/// with every message, else `logs.json` with the prompts only. The directory
+/// is named after a hash of the project path, so the project is the first
+/// tracked path a tool worked on there.
+pub struct GeminiSource {
+    files: LogFiles,
+}
+
+impl GeminiSource {
+    pub fn from_config(config: &LogSourceConfig) -> Self {
+        Self { files: LogFiles::from_config(config, &["~/.gemini/tmp"]) }
+    }
+}
+
+/// Messages of a saved chat, with an empty project.
+fn parse_gemini_chat(content: &str) -> Vec<TimestampRecord> {
+    let Ok(chat) = serde_json::from_str::<Value>(content) else { return vec![] };
+    let Some(messages) = chat["messages"].as_array() else { return vec![] };
+
+    messages
+        .iter()
+        .filter_map(|message| {
+            let timestamp = message["timestamp"].as_str().and_then(parse_timestamp)?;
+            let calls = message["toolCalls"].as_array().map(Vec::as_slice).unwrap_or_default();
+            let kind = match message["type"].as_str() {
+                Some("user") => EventKind::Prompt,
+                Some("gemini") if !calls.is_empty() => EventKind::Tool,
+                Some("gemini") => EventKind::Assistant,
+                _ => EventKind::Other,
+            };
+            Some(TimestampRecord {
+                path: calls.iter().find_map(|c| argument_path(&c["args"])),
+                details: EventDetails {
+                    tools: calls.iter().filter_map(|c| c["name"].as_str().map(str::to_string)).collect(),
+                    model: message["model"].as_str().map(str::to_string),
+                    branch: None,
+                },
+                ..TimestampRecord::new(timestamp, String::new(), kind)
+            })
+        })
+        .collect()
+}
+
+/// Prompts from `logs.json`.
+fn parse_gemini_log(content: &str) -> Vec<TimestampRecord> {
+    let Ok(Value::Array(entries)) = serde_json::from_str::<Value>(content) else { return vec![] };
+    entries
+        .iter()
+        .filter(|e| e["type"] == "user")
+        .filter_map(|e| e["timestamp"].as_str().and_then(parse_timestamp))
+        .map(|t| TimestampRecord::new(t, String::new(), EventKind::Prompt))
+        .collect()
+}
+
+impl ActivitySource for GeminiSource {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn records(&self, scope: Scope, projects: &ProjectResolver, debug: bool) -> Vec<TimestampRecord> {
+        let mut records = Vec::new();
+        let mut seen = SeenRecords::default();
+        let project_dirs = self
+            .files
+            .dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir());
+
+        for dir in project_dirs {
+            // Chats are keyed by file name, the prompt log by the project hash.
+            let mut logs: Vec<(String, Vec<TimestampRecord>)> = find_log_files(&[dir.join("chats")], "json", scope, debug)
+                .into_iter()
+                .filter(|f| self.files.is_wanted(f))
+                .filter_map(|f| Some((file_session(&f), parse_gemini_chat(&fs::read_to_string(&f).ok()?))))
+                .collect();
+            let log = dir.join("logs.json");
+            if logs.is_empty()
+                && self.files.is_wanted(&log)
+                && let Ok(content) = fs::read_to_string(&log)
+            {
+                let hash = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                logs.push((hash, parse_gemini_log(&content)));
+            }
+
+            let project = logs
+                .iter()
+                .flat_map(|(_, found)| found)
+                .filter_map(|r| r.path.as_deref())
+                .find_map(|p| projects.key_for_path(p))
+                .unwrap_or_else(|| "unknown".to_string());
+            for (session, found) in logs {
+                seen.next_file();
+                records.extend(
+                    found
+                        .into_iter()
+                        .filter(|r| scope.includes(r.timestamp) && seen.is_new(format!("{}|{}", session, r.timestamp)))
+                        .map(|r| TimestampRecord { project: project.clone(), ..r }),
+                );
+            }
+        }
+
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_codex() {
+        let content = [
+            r#"{"timestamp":"2026-01-20T18:00:00.000Z","type":"session_meta","payload":{"id":"x","cwd":"/home/ola/code/app"}}"#,
+            r#"{"timestamp":"2026-01-20T18:00:01.000Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"<environment_context>...</environment_context>"}]}}"#,
+            r#"{"timestamp":"2026-01-20T18:00:02.000Z","type":"turn_context","payload":{"cwd":"/home/ola/code/app","model":"gpt-5-codex"}}"#,
+            r#"{"timestamp":"2026-01-20T18:00:03.000Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"fix the build"}]}}"#,
+            r#"{"timestamp":"2026-01-20T18:00:04.000Z","type":"event_msg","payload":{"type":"user_message","message":"fix the build"}}"#,
+            r#"{"timestamp":"2026-01-20T18:00:09.000Z","type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"cargo\",\"build\"],\"workdir\":\"/home/ola/code/app\"}"}}"#,
+            r#"{"timestamp":"2026-01-20T18:00:20.000Z","type":"response_item","payload":{"type":"function_call_output","output":"ok"}}"#,
+        ]
+        .join("\n");
+
+        let (cwd, records) = parse_codex(&content);
+        assert_eq!(cwd.as_deref(), Some("/home/ola/code/app"));
+        let kinds: Vec<_> = records.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, [EventKind::Other, EventKind::Other, EventKind::Other, EventKind::Prompt, EventKind::Tool, EventKind::Tool]);
+        assert_eq!(records[4].details.tools, ["shell"]);
+        assert_eq!(records[4].details.model.as_deref(), Some("gpt-5-codex"));
+        assert_eq!(records[4].path.as_deref(), Some("/home/ola/code/app"));
+    }
+
+    #[test]
+    fn test_parse_aider() {
+        let content = "\n# 2026-01-20 19:00:01.123456\n+add tests\n+for the parser\n\n# 2026-01-20 19:05:00.000001\n+/run cargo test\n";
+        // Warsaw is UTC+1 in January.
+        let expected: Vec<NaiveDateTime> = ["2026-01-20 18:00:01.123456", "2026-01-20 18:05:00.000001"]
+            .iter()
+            .map(|s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f").unwrap())
+            .collect();
+        assert_eq!(parse_aider(content, &TimeZones::fixed(chrono_tz::Europe::Warsaw)), expected);
+    }
+
+    #[test]
+    fn test_parse_gemini() {
+        let chat = r#"{"sessionId":"s","projectHash":"ab12","messages":[
+            {"id":"1","timestamp":"2026-01-20T18:00:00.000Z","type":"user","content":"refactor"},
+            {"id":"2","timestamp":"2026-01-20T18:00:30.000Z","type":"gemini","content":"","model":"gemini-2.5-pro",
+             "toolCalls":[{"name":"read_file","args":{"absolute_path":"/srv/work/api/src/lib.rs"}}]},
+            {"id":"3","timestamp":"2026-01-20T18:01:00.000Z","type":"gemini","content":"Done","model":"gemini-2.5-pro"}
+        ]}"#;
+        let records = parse_gemini_chat(chat);
+        assert_eq!(records.iter().map(|r| r.kind).collect::<Vec<_>>(), [EventKind::Prompt, EventKind::Tool, EventKind::Assistant]);
+        assert_eq!(records[1].path.as_deref(), Some("/srv/work/api/src/lib.rs"));
+        assert_eq!(records[1].details.tools, ["read_file"]);
+
+        let log = r#"[{"sessionId":"s","messageId":0,"type":"user","message":"refactor","timestamp":"2026-01-20T18:00:00.000Z"}]"#;
+        assert_eq!(parse_gemini_log(log).len(), 1);
+    }
+}
@@ -0,0 +1,234 @@
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::git;
+use crate::jsonl::{ProjectHours, Session, SessionRules, AGENT_PROJECT};
+use crate::overtime::{calculate_session_overtime, day_blocks, overtime_blocks};
+use crate::schedule::Schedule;
+use crate::sources::source_label;
+
+/// Row for sessions of the project whose records touched no paths.
+pub const NO_PATHS: &str = "(bez ścieżek)";
+/// Row for files lying directly in the project root.
+pub const PROJECT_ROOT: &str = "(katalog główny)";
+
+/// Overtime of `project` (a name as shown in reports) per directory `depth`
+/// levels below its root, for days in `from..=to`.
+///
+/// A session's overtime is split between projects exactly like in the project
+/// tables; the project's part is then split between directories by how many
+/// records touched paths in them.
+pub fn path_breakdown(
+    sessions: &[Session],
+    schedule: &Schedule,
+    rules: &SessionRules,
+    project: &str,
+    depth: usize,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> HashMap<String, ProjectHours> {
+    let mut result: HashMap<String, ProjectHours> = HashMap::new();
+
+    for session in sessions {
+        let dirs = session_dirs(session, rules, project, depth);
+        let dir_total: usize = dirs.values().sum();
+        let blocks = overtime_blocks(session, schedule);
+
+        for (date, hours) in calculate_session_overtime(session, schedule, false) {
+            if date < from || date > to || hours <= 0.0 {
+                continue;
+            }
+            let weights: HashMap<String, f64> = if session.agent_driven {
+                HashMap::from([(AGENT_PROJECT.to_string(), 1.0)])
+            } else {
+                session.project_weights(rules.attribution, &day_blocks(&blocks, date))
+            };
+            let total_weight: f64 = weights.values().sum();
+            let project_weight: f64 = weights
+                .iter()
+                .filter(|(key, _)| rules.projects.display(key) == project)
+                .map(|(_, weight)| weight)
+                .sum();
+            if total_weight <= 0.0 || project_weight <= 0.0 {
+                continue;
+            }
+            let project_hours = hours * project_weight / total_weight;
+            let parts: Vec<(&str, f64)> = if dir_total == 0 {
+                vec![(NO_PATHS, project_hours)]
+            } else {
+                dirs.iter()
+                    .map(|(dir, count)| (dir.as_str(), project_hours * *count as f64 / dir_total as f64))
+                    .collect()
+            };
+
+            for (dir, part) in parts {
+                let entry = result.entry(dir.to_string()).or_default();
+                if schedule.is_weekend_rate(date) {
+                    entry.weekend_hours += part;
+                } else {
+                    entry.weekday_hours += part;
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Overtime per activity source (tool), for days in `from..=to`. A session
+/// mixing sources is split by how many of its records each one produced.
+pub fn tool_breakdown(sessions: &[Session], schedule: &Schedule, from: NaiveDate, to: NaiveDate) -> HashMap<String, ProjectHours> {
+    let mut result: HashMap<String, ProjectHours> = HashMap::new();
+
+    for session in sessions {
+        let total: usize = session.stats.sources.values().sum();
+        if total == 0 {
+            continue;
+        }
+
+        for (date, hours) in calculate_session_overtime(session, schedule, false) {
+            if date < from || date > to || hours <= 0.0 {
+                continue;
+            }
+            for (source, count) in &session.stats.sources {
+                let part = hours * *count as f64 / total as f64;
+                let entry = result.entry(source_label(source).to_string()).or_default();
+                if schedule.is_weekend_rate(date) {
+                    entry.weekend_hours += part;
+                } else {
+                    entry.weekday_hours += part;
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Record counts per directory for the session's paths inside `project`.
+fn session_dirs(session: &Session, rules: &SessionRules, project: &str, depth: usize) -> HashMap<String, usize> {
+    let roots: Vec<PathBuf> = session
+        .project_counts
+        .keys()
+        .filter(|key| key.starts_with('/'))
+        .map(PathBuf::from)
+        .collect();
+
+    let mut dirs: HashMap<String, usize> = HashMap::new();
+    for (path, count) in &session.path_counts {
+        let key = rules.projects.repo_identity(path).unwrap_or_else(|| path.clone());
+        if rules.projects.display(&key) != project {
+            continue;
+        }
+        let mut candidates = roots.clone();
+        if let Some(repo) = git::find_repo(Path::new(path)) {
+            candidates.push(repo.workdir);
+        }
+        *dirs.entry(relative_dir(Path::new(path), &candidates, project, depth)).or_insert(0) += count;
+    }
+    dirs
+}
+
+/// First `depth` directories of `path` below the closest of `roots`, or below
+/// a component named like the project when no root contains it. File names are
+/// dropped; a path that no longer exists counts as a file.
+fn relative_dir(path: &Path, roots: &[PathBuf], project: &str, depth: usize) -> String {
+    let relative = roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .min_by_key(|rest| rest.components().count())
+        .map(Path::to_path_buf)
+        .or_else(|| {
+            let components: Vec<_> = path.components().collect();
+            let position = components.iter().position(|c| c.as_os_str() == project)?;
+            Some(components[position + 1..].iter().collect())
+        })
+        .unwrap_or_else(|| path.to_path_buf());
+
+    let dir = if path.is_dir() {
+        relative.as_path()
+    } else {
+        relative.parent().unwrap_or(Path::new(""))
+    };
+
+    let parts: Vec<String> = dir
+        .components()
+        .take(depth)
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if parts.is_empty() {
+        PROJECT_ROOT.to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timezone::TimeZones;
+    use chrono::NaiveDateTime;
+
+    fn warsaw() -> Schedule {
+        Schedule::default().with_time_zones(TimeZones::fixed(chrono_tz::Europe::Warsaw))
+    }
+
+    #[test]
+    fn test_relative_dir() {
+        let roots = vec![PathBuf::from("/home/jarx/Programowanie/farmaster2")];
+        let dir = |path: &str, depth| relative_dir(Path::new(path), &roots, "farmaster2", depth);
+
+        assert_eq!(dir("/home/jarx/Programowanie/farmaster2/packages/api/src/lib.rs", 2), "packages/api");
+        assert_eq!(dir("/home/jarx/Programowanie/farmaster2/packages/api/src/lib.rs", 1), "packages");
+        assert_eq!(dir("/home/jarx/Programowanie/farmaster2/Cargo.toml", 2), PROJECT_ROOT);
+        assert_eq!(dir("/mnt/backup/farmaster2/docs/plan.md", 2), "docs");
+    }
+
+    #[test]
+    fn test_split_follows_project_share() {
+        // Sunday 2025-08-10 18:00-20:00 Warsaw: two hours of weekend overtime,
+        // three quarters of it for farmaster2.
+        let at = |h| NaiveDate::from_ymd_opt(2025, 8, 10).unwrap().and_hms_opt(h, 0, 0).unwrap();
+        let (start, end): (NaiveDateTime, NaiveDateTime) = (at(16), at(18));
+        let root = "/home/jarx/Programowanie/farmaster2";
+        let session = Session {
+            project_counts: HashMap::from([
+                (root.to_string(), 3),
+                ("/home/jarx/Programowanie/after15-core".to_string(), 1),
+            ]),
+            path_counts: HashMap::from([
+                (format!("{}/web/app.ts", root), 1),
+                (format!("{}/api/main.rs", root), 2),
+                ("/home/jarx/Programowanie/after15-core/src/main.rs".to_string(), 1),
+            ]),
+            ..Session::spanning(start, end)
+        };
+
+        let rules = SessionRules::default();
+        let day = start.date();
+        let rows = path_breakdown(&[session], &warsaw(), &rules, "farmaster2", 1, day, day);
+
+        assert_eq!(rows.len(), 2);
+        assert!((rows["api"].weekend_hours - 1.0).abs() < 1e-9);
+        assert!((rows["web"].weekend_hours - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tool_breakdown() {
+        // Same Sunday evening, two hours: 3 Claude Code records to 1 Codex one.
+        let at = |h| NaiveDate::from_ymd_opt(2025, 8, 10).unwrap().and_hms_opt(h, 0, 0).unwrap();
+        let (start, end) = (at(16), at(18));
+        let mut session = Session {
+            project_counts: HashMap::from([("/srv/app".to_string(), 4)]),
+            ..Session::spanning(start, end)
+        };
+        session.stats.sources = [("claude", 3), ("codex", 1)].into_iter().collect();
+
+        let day = start.date();
+        let rows = tool_breakdown(&[session], &warsaw(), day, day);
+
+        assert!((rows["Claude Code"].weekend_hours - 1.5).abs() < 1e-9);
+        assert!((rows["Codex CLI"].weekend_hours - 0.5).abs() < 1e-9);
+    }
+}
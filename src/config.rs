@@ -1,5 +1,11 @@
+use chrono::NaiveDate;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
+use std::path::PathBuf;
+
+use crate::projects::expand_home;
+use crate::schedule::ShiftType;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct SalaryConfig {
@@ -20,11 +26,60 @@ impl Default for SalaryConfig {
     }
 }
 
+/// How a session's time is divided between the projects seen in it.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Attribution {
+    /// By number of log records (the original method).
+    #[default]
+    Records,
+    /// By active time inside the overtime intervals.
+    Time,
+}
+
+/// What identifies a project when its path lies inside a git repository.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GitIdentity {
+    /// The path itself; repositories are ignored.
+    Off,
+    /// The repository's top-level directory (the main checkout for worktrees).
+    #[default]
+    Toplevel,
+    /// The `origin` remote URL, falling back to the top-level directory.
+    Remote,
+}
+
+/// Path pattern naming a project: a `glob` (each `*`, `**`, `?` is a capture
+/// group) or a `regex`; `name` may use `$1` or `${group}`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ProjectRule {
+    #[serde(default)]
+    pub glob: Option<String>,
+    #[serde(default)]
+    pub regex: Option<String>,
+    pub name: String,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProjectsConfig {
+    /// Path fragment; the directory right below it is the project.
     pub tracked_path: String,
     #[serde(default)]
     pub excluded_projects: Vec<String>,
+    #[serde(default)]
+    pub attribution: Attribution,
+    /// Directories whose direct subdirectories are projects, e.g. "~/code".
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// Tried in order before `roots` and `tracked_path`; the first match wins.
+    #[serde(default)]
+    pub rules: Vec<ProjectRule>,
+    /// Alternative name -> project it belongs to.
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    #[serde(default)]
+    pub git_identity: GitIdentity,
 }
 
 impl Default for ProjectsConfig {
@@ -32,16 +87,313 @@ impl Default for ProjectsConfig {
         Self {
             tracked_path: "Programowanie".to_string(),
             excluded_projects: vec![],
+            attribution: Attribution::default(),
+            roots: vec![],
+            rules: vec![],
+            aliases: HashMap::new(),
+            git_identity: GitIdentity::default(),
+        }
+    }
+}
+
+/// One block of the shift rotation: a whole week (seven days, counted from
+/// the first day of the block) or a single day.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationTemplate {
+    Week([String; 7]),
+    Day(String),
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExtraHoliday {
+    pub date: NaiveDate,
+    #[serde(default = "default_extra_holiday_name")]
+    pub name: String,
+}
+
+fn default_extra_holiday_name() -> String {
+    "Dzień wolny".to_string()
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct HolidaysConfig {
+    /// Built-in Polish statutory holidays, including the Easter-based ones.
+    pub polish: bool,
+    /// Additional days off, e.g. company holidays.
+    pub extra: Vec<ExtraHoliday>,
+}
+
+impl Default for HolidaysConfig {
+    fn default() -> Self {
+        Self {
+            polish: true,
+            extra: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScheduleConfig {
+    /// First day of the rotation; earlier days are regular weekdays and weekends.
+    pub anchor: NaiveDate,
+    /// Extra shift types; an entry with a built-in name replaces the built-in.
+    #[serde(default)]
+    pub shift_types: Vec<ShiftType>,
+    pub rotation: Vec<RotationTemplate>,
+    #[serde(default)]
+    pub holidays: HolidaysConfig,
+    /// Maps event summaries of an imported .ics rota to shift type names.
+    #[serde(default)]
+    pub ics_mapping: HashMap<String, String>,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        let week = |days: [&str; 7]| RotationTemplate::Week(days.map(String::from));
+
+        Self {
+            anchor: NaiveDate::from_ymd_opt(2025, 7, 28).unwrap(),
+            shift_types: vec![],
+            rotation: vec![
+                week(["afternoon", "afternoon", "afternoon", "afternoon", "afternoon", "saturday_afternoon", "weekend"]),
+                week(["regular", "regular", "regular", "regular", "regular", "weekend", "weekend"]),
+                week(["regular", "regular", "regular", "regular", "regular", "weekend", "weekend"]),
+            ],
+            holidays: HolidaysConfig::default(),
+            ics_mapping: HashMap::new(),
+        }
+    }
+}
+
+/// Per-project replacement for the session thresholds; unset fields keep the global value.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct SessionThresholdsOverride {
+    pub gap_minutes: Option<u32>,
+    pub min_session_minutes: Option<u32>,
+}
+
+/// How much of the gap after an event counts as work, by event kind (0.0-1.0).
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ActivityConfig {
+    pub prompt_weight: f64,
+    pub tool_weight: f64,
+    pub assistant_weight: f64,
+    /// Most that is credited after an assistant response, however long the pause.
+    pub assistant_cap_minutes: Option<u32>,
+}
+
+impl Default for ActivityConfig {
+    fn default() -> Self {
+        Self {
+            prompt_weight: 1.0,
+            tool_weight: 1.0,
+            assistant_weight: 1.0,
+            assistant_cap_minutes: None,
+        }
+    }
+}
+
+/// Whether an activity source is read and how much the pauses after its
+/// records count (0.0-1.0, on top of `sessions.activity`).
+#[derive(Debug, Clone)]
+pub struct SourceSettings {
+    pub enabled: bool,
+    pub weight: f64,
+}
+
+/// Logs of an AI coding assistant. `dirs` replaces the tool's default log
+/// locations, e.g. to add logs synced from another machine.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LogSourceConfig {
+    /// Unset = the source's default (only Claude Code is read by default).
+    pub enabled: Option<bool>,
+    pub weight: f64,
+    pub dirs: Vec<String>,
+    /// Globs a log file must match (any of them); empty = every file.
+    pub include: Vec<String>,
+    /// Globs of log files to skip.
+    pub exclude: Vec<String>,
+}
+
+impl Default for LogSourceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: None,
+            weight: 1.0,
+            dirs: vec![],
+            include: vec![],
+            exclude: vec![],
+        }
+    }
+}
+
+impl LogSourceConfig {
+    pub fn settings(&self, enabled_by_default: bool) -> SourceSettings {
+        SourceSettings {
+            enabled: self.enabled.unwrap_or(enabled_by_default),
+            weight: self.weight,
+        }
+    }
+
+    /// `dirs` with `~` expanded, or `defaults` when none are configured.
+    pub fn search_dirs(&self, defaults: &[&str]) -> Vec<PathBuf> {
+        let dirs: Vec<&str> = if self.dirs.is_empty() {
+            defaults.to_vec()
+        } else {
+            self.dirs.iter().map(String::as_str).collect()
+        };
+        dirs.into_iter().map(|d| PathBuf::from(expand_home(d))).collect()
+    }
+}
+
+/// Local repositories whose commits count as activity.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct GitSourceConfig {
+    pub enabled: bool,
+    pub weight: f64,
+    pub repos: Vec<String>,
+    /// Author/committer e-mails counted as yours; empty = `user.email` of each repo.
+    pub emails: Vec<String>,
+}
+
+impl Default for GitSourceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weight: 1.0,
+            repos: vec![],
+            emails: vec![],
+        }
+    }
+}
+
+impl GitSourceConfig {
+    pub fn settings(&self) -> SourceSettings {
+        SourceSettings {
+            enabled: self.enabled,
+            weight: self.weight,
         }
     }
 }
 
+/// Timestamped shell history files.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ShellSourceConfig {
+    pub enabled: bool,
+    pub weight: f64,
+    /// History files; empty = the default zsh, bash and fish locations.
+    pub files: Vec<String>,
+}
+
+impl Default for ShellSourceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weight: 1.0,
+            files: vec![],
+        }
+    }
+}
+
+impl ShellSourceConfig {
+    pub fn settings(&self) -> SourceSettings {
+        SourceSettings {
+            enabled: self.enabled,
+            weight: self.weight,
+        }
+    }
+}
+
+/// Activity sources merged into one timeline before sessions are built.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SourcesConfig {
+    pub claude: LogSourceConfig,
+    pub codex: LogSourceConfig,
+    pub aider: LogSourceConfig,
+    pub gemini: LogSourceConfig,
+    pub git: GitSourceConfig,
+    pub shell: ShellSourceConfig,
+}
+
+/// How sub-agent transcripts (`.../subagents/*.jsonl`) count.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SubagentPolicy {
+    /// Read and linked to their parent session, but left out of the totals.
+    #[default]
+    Ignore,
+    /// Part of the main timeline, like any other record.
+    Merge,
+    /// Own sessions outside the main ones, reported as "Agenci".
+    Separate,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SessionsConfig {
+    /// A pause longer than this ends a session.
+    pub gap_minutes: u32,
+    /// Shorter sessions are ignored (checked after padding).
+    pub min_session_minutes: u32,
+    /// Estimated thinking time credited before the first event of a session.
+    pub padding_before_minutes: u32,
+    /// Estimated reading time credited after the last event of a session.
+    pub padding_after_minutes: u32,
+    /// Every active burst counts at least this long, measured plus estimated.
+    pub min_burst_minutes: u32,
+    pub activity: ActivityConfig,
+    pub subagents: SubagentPolicy,
+    /// Keyed by project name as shown in reports, or by project key (a path).
+    pub projects: HashMap<String, SessionThresholdsOverride>,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            gap_minutes: 30,
+            min_session_minutes: 5,
+            padding_before_minutes: 0,
+            padding_after_minutes: 0,
+            min_burst_minutes: 0,
+            activity: ActivityConfig::default(),
+            subagents: SubagentPolicy::default(),
+            projects: HashMap::new(),
+        }
+    }
+}
+
+/// A date range (inclusive) evaluated in a different time zone, e.g. a business trip.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TimeZonePeriod {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub timezone: String,
+}
+
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct Config {
     #[serde(default)]
     pub salary: SalaryConfig,
     #[serde(default)]
     pub projects: ProjectsConfig,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+    /// IANA time zone name; the system zone when not set.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub timezone_periods: Vec<TimeZonePeriod>,
+    #[serde(default)]
+    pub sources: SourcesConfig,
 }
 
 impl Config {
@@ -58,19 +410,21 @@ impl Config {
     }
 }
 
+pub fn config_file_path(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir()
+        .map(|p| p.join("after15").join(file_name))
+        .or_else(|| dirs::home_dir().map(|p| p.join(".config/after15").join(file_name)))
+}
+
 pub fn load_config() -> Config {
-    let config_path = dirs::config_dir()
-        .map(|p| p.join("after15/config.json"))
-        .or_else(|| dirs::home_dir().map(|p| p.join(".config/after15/config.json")));
-
-    if let Some(path) = config_path {
-        if path.exists() {
-            if let Ok(content) = fs::read_to_string(&path) {
-                if let Ok(config) = serde_json::from_str(&content) {
-                    return config;
-                }
-            }
-        }
+    let config_path = config_file_path("config.json");
+
+    if let Some(path) = config_path
+        && path.exists()
+        && let Ok(content) = fs::read_to_string(&path)
+        && let Ok(config) = serde_json::from_str(&content)
+    {
+        return config;
     }
 
     Config::default()
@@ -0,0 +1,157 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::ics::{write_calendar, OutputEvent};
+use crate::jsonl::{load_sessions_for_range, Session, SessionRules};
+use crate::sources::Sources;
+use crate::overtime::overtime_blocks;
+use crate::schedule::Schedule;
+
+pub fn parse_month(month: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| format!("Nieprawidłowy format miesiąca: {} (użyj YYYY-MM)", month))?;
+    let next_month = if first.month() == 12 {
+        NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+    }
+    .unwrap();
+    Ok((first, next_month - Duration::days(1)))
+}
+
+/// Project names of a session with their share of `intervals` (or of its
+/// records, see `Session::project_weights`), largest first.
+fn project_shares(session: &Session, rules: &SessionRules, intervals: &[(NaiveDateTime, NaiveDateTime)]) -> Vec<(String, f64)> {
+    let real_projects = session.project_weights(rules.attribution, intervals);
+    let total: f64 = real_projects.values().sum();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut shares: Vec<(String, f64)> = Vec::new();
+    for (name, weight) in real_projects {
+        let display = rules.projects.display(&name);
+        let share = weight / total;
+        match shares.iter_mut().find(|(n, _)| *n == display) {
+            Some(existing) => existing.1 += share,
+            None => shares.push((display, share)),
+        }
+    }
+    shares.sort_by(|a, b| b.1.total_cmp(&a.1));
+    shares
+}
+
+/// One VEVENT per overtime block of every session in the month.
+pub fn overtime_events(
+    sessions: &[Session],
+    schedule: &Schedule,
+    rules: &SessionRules,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<OutputEvent> {
+    let mut events = Vec::new();
+
+    for session in sessions {
+        let session_start = schedule.to_local(session.start_time);
+        let session_end = schedule.to_local(session.end_time);
+
+        for (date, start_utc, end_utc) in overtime_blocks(session, schedule) {
+            if date < from || date > to {
+                continue;
+            }
+
+            let shares = project_shares(session, rules, &[(start_utc, end_utc)]);
+            let projects = if shares.is_empty() {
+                "Inne".to_string()
+            } else {
+                shares
+                    .iter()
+                    .map(|(name, share)| format!("{} ({:.0}%)", name, share * 100.0))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let main_project = shares.first().map(|(n, _)| n.as_str()).unwrap_or("Inne");
+            let minutes = ((end_utc - start_utc).num_seconds() as f64 / 60.0).round() as i64;
+
+            events.push(OutputEvent {
+                uid: format!("{}-{}@after15", start_utc.format("%Y%m%dT%H%M%S"), end_utc.format("%H%M%S")),
+                start: start_utc,
+                end: end_utc,
+                summary: format!("Nadgodziny: {} ({} min)", main_project, minutes),
+                description: format!(
+                    "Sesja: {} → {}\nProjekty: {}\nNadgodziny: {} min",
+                    session_start.format("%Y-%m-%d %H:%M"),
+                    session_end.format("%Y-%m-%d %H:%M"),
+                    projects,
+                    minutes
+                ),
+            });
+        }
+    }
+
+    events.sort_by_key(|e| e.start);
+    events
+}
+
+pub fn export_ics(
+    sources: &Sources,
+    schedule: &Schedule,
+    rules: &SessionRules,
+    month: &str,
+    output: Option<PathBuf>,
+    debug: bool,
+) -> Result<(PathBuf, usize), String> {
+    let (from, to) = parse_month(month)?;
+    let sessions = load_sessions_for_range(from, to, sources, schedule, rules, debug);
+    let events = overtime_events(&sessions, schedule, rules, from, to);
+
+    let calendar = write_calendar(
+        &format!("Nadgodziny {}", month),
+        &events,
+        Utc::now().naive_utc(),
+    );
+
+    let output_path = output.unwrap_or_else(|| {
+        let filename = format!("nadgodziny_{}.ics", month);
+        dirs::home_dir()
+            .map(|home| home.join(&filename))
+            .unwrap_or_else(|| PathBuf::from(&filename))
+    });
+
+    fs::write(&output_path, calendar)
+        .map_err(|e| format!("Nie można zapisać {}: {}", output_path.display(), e))?;
+
+    Ok((output_path, events.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timezone::TimeZones;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_overtime_events() {
+        // 14:00-17:00 Warsaw on a regular Monday: 15:00-17:00 is overtime.
+        let start = NaiveDate::from_ymd_opt(2025, 8, 4).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let end = start + Duration::hours(3);
+        let session = Session {
+            project_counts: HashMap::from([
+                ("-home-jarx-Programowanie-farmaster2".to_string(), 3),
+                ("-home-jarx-Programowanie-after15-core".to_string(), 1),
+            ]),
+            ..Session::spanning(start, end)
+        };
+
+        let (from, to) = parse_month("2025-08").unwrap();
+        let schedule = Schedule::default().with_time_zones(TimeZones::fixed(chrono_tz::Europe::Warsaw));
+        let events = overtime_events(&[session], &schedule, &SessionRules::default(), from, to);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].start, start + Duration::hours(1));
+        assert_eq!(events[0].end, end);
+        assert_eq!(events[0].summary, "Nadgodziny: farmaster2 (120 min)");
+        assert!(events[0].description.contains("farmaster2 (75%), after15-core (25%)"));
+    }
+}
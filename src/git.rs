@@ -0,0 +1,313 @@
+use chrono::{DateTime, NaiveDateTime};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::GitSourceConfig;
+use crate::jsonl::{EventKind, TimestampRecord};
+use crate::projects::{expand_home, ProjectResolver};
+use crate::sources::{ActivitySource, Scope};
+
+/// A git repository found on disk, read directly from its files (no `git` binary).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repo {
+    /// Checkout the path is in: the main one or a linked worktree.
+    pub workdir: PathBuf,
+    /// Working tree of the main checkout; linked worktrees map to it too.
+    pub toplevel: PathBuf,
+    /// Directory holding `config`, `refs` and objects shared by all worktrees.
+    pub common_dir: PathBuf,
+}
+
+/// Repository enclosing `path`. The path itself does not have to exist
+/// (files from old logs may be gone), only one of its ancestors.
+pub fn find_repo(path: &Path) -> Option<Repo> {
+    path.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(Repo {
+                workdir: dir.to_path_buf(),
+                toplevel: dir.to_path_buf(),
+                common_dir: dot_git,
+            });
+        }
+        if dot_git.is_file() {
+            return Some(linked_repo(dir, &dot_git));
+        }
+        None
+    })
+}
+
+/// `.git` as a file: a linked worktree (`gitdir: <main>/.git/worktrees/<name>`)
+/// or a submodule, which stays a repository of its own.
+fn linked_repo(dir: &Path, dot_git: &Path) -> Repo {
+    let own = Repo {
+        workdir: dir.to_path_buf(),
+        toplevel: dir.to_path_buf(),
+        common_dir: dot_git.to_path_buf(),
+    };
+    let Some(git_dir) = fs::read_to_string(dot_git)
+        .ok()
+        .and_then(|s| s.trim().strip_prefix("gitdir:").map(|d| dir.join(d.trim())))
+    else {
+        return own;
+    };
+
+    let Ok(common) = fs::read_to_string(git_dir.join("commondir")) else {
+        return Repo { common_dir: git_dir, ..own };
+    };
+    let common_dir = normalize_path(&git_dir.join(common.trim()));
+    match common_dir.parent() {
+        Some(main) if common_dir.file_name().is_some_and(|n| n == ".git") => Repo {
+            toplevel: main.to_path_buf(),
+            common_dir,
+            ..own
+        },
+        _ => Repo { common_dir, ..own },
+    }
+}
+
+/// Resolves `.` and `..` without touching the filesystem.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                result.pop();
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+impl Repo {
+    /// URL of `origin` (or the first remote), normalized by [`normalize_remote`].
+    pub fn remote(&self) -> Option<String> {
+        let config = fs::read_to_string(self.common_dir.join("config")).ok()?;
+        let mut remotes: Vec<(String, String)> = Vec::new();
+        let mut section: Option<String> = None;
+
+        for line in config.lines().map(str::trim) {
+            if line.starts_with('[') {
+                section = line
+                    .strip_prefix("[remote \"")
+                    .and_then(|rest| rest.strip_suffix("\"]"))
+                    .map(str::to_string);
+            } else if let Some(name) = &section
+                && let Some((key, value)) = line.split_once('=')
+                && key.trim() == "url"
+            {
+                remotes.push((name.clone(), value.trim().to_string()));
+            }
+        }
+
+        remotes
+            .iter()
+            .find(|(name, _)| name == "origin")
+            .or(remotes.first())
+            .map(|(_, url)| normalize_remote(url))
+    }
+}
+
+/// `git@github.com:me/repo.git` and `https://github.com/me/repo` both become
+/// `github.com/me/repo`, so clones over different protocols compare equal.
+pub fn normalize_remote(url: &str) -> String {
+    let strip_user = |s: &str| -> String {
+        s.split_once('@')
+            .filter(|(user, _)| !user.contains('/'))
+            .map(|(_, rest)| rest)
+            .unwrap_or(s)
+            .to_string()
+    };
+
+    let normalized = match url.split_once("://") {
+        // URL syntax: the port is not part of the identity.
+        Some((_, rest)) => {
+            let rest = strip_user(rest);
+            let (host, path) = rest.split_once('/').unwrap_or((&rest, ""));
+            let host = host.split(':').next().unwrap_or(host);
+            format!("{}/{}", host, path)
+        }
+        // scp-like syntax: host:path
+        None => {
+            let rest = strip_user(url);
+            match rest.split_once(':') {
+                Some((host, path)) if !host.contains('/') => format!("{}/{}", host, path.trim_start_matches('/')),
+                _ => rest,
+            }
+        }
+    };
+    normalized.trim_end_matches('/').trim_end_matches(".git").to_string()
+}
+
+/// Commits in local repositories: the author time and, when it differs, the
+/// commit time (rebases, amends) of every commit by one of your e-mails.
+/// Reads history with the `git` binary; a missing binary or repository gives
+/// no records.
+pub struct GitSource {
+    repos: Vec<PathBuf>,
+    emails: Vec<String>,
+}
+
+impl GitSource {
+    pub fn from_config(config: &GitSourceConfig) -> Self {
+        Self {
+            repos: config.repos.iter().map(|r| PathBuf::from(expand_home(r))).collect(),
+            emails: config.emails.iter().map(|e| e.to_lowercase()).collect(),
+        }
+    }
+
+    fn emails_for(&self, repo: &Path) -> Vec<String> {
+        if !self.emails.is_empty() {
+            return self.emails.clone();
+        }
+        Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["config", "user.email"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| vec![String::from_utf8_lossy(&o.stdout).trim().to_lowercase()])
+            .unwrap_or_default()
+    }
+}
+
+/// Output of `git log --format=%at%x09%ae%x09%ct%x09%ce`: UTC times of your
+/// authoring and committing, one per distinct instant.
+fn parse_log(output: &str, emails: &[String]) -> Vec<NaiveDateTime> {
+    let mine = |email: &str| emails.iter().any(|e| e.eq_ignore_ascii_case(email));
+    let time = |secs: &str| secs.parse::<i64>().ok().and_then(|s| DateTime::from_timestamp(s, 0)).map(|t| t.naive_utc());
+
+    let mut times = Vec::new();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [author_time, author, commit_time, committer] = fields[..] else {
+            continue;
+        };
+        let authored = time(author_time).filter(|_| mine(author));
+        let committed = time(commit_time).filter(|_| mine(committer));
+        times.extend(authored);
+        times.extend(committed.filter(|c| Some(*c) != authored));
+    }
+    times
+}
+
+impl ActivitySource for GitSource {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn records(&self, scope: Scope, projects: &ProjectResolver, debug: bool) -> Vec<TimestampRecord> {
+        let mut records = Vec::new();
+
+        for repo in &self.repos {
+            let emails = self.emails_for(repo);
+            let mut command = Command::new("git");
+            command
+                .arg("-C")
+                .arg(repo)
+                .args(["log", "--all", "--format=%at%x09%ae%x09%ct%x09%ce"]);
+            match scope {
+                Scope::Since(start) => {
+                    command.arg(format!("--since={} +0000", start.format("%Y-%m-%d %H:%M:%S")));
+                }
+                Scope::All => {}
+            }
+
+            let output = match command.output() {
+                Ok(output) if output.status.success() => output,
+                Ok(output) => {
+                    if debug {
+                        eprintln!("[DEBUG] git log failed in {}: {}", repo.display(), String::from_utf8_lossy(&output.stderr).trim());
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    if debug {
+                        eprintln!("[DEBUG] Cannot run git: {}", e);
+                    }
+                    continue;
+                }
+            };
+
+            let path = repo.to_string_lossy().to_string();
+            let project = projects.repo_identity(&path).unwrap_or(path);
+            for timestamp in parse_log(&String::from_utf8_lossy(&output.stdout), &emails) {
+                records.push(TimestampRecord::new(timestamp, project.clone(), EventKind::Other));
+            }
+        }
+
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProjectsConfig;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn test_normalize_remote() {
+        assert_eq!(normalize_remote("git@github.com:jarx/farmaster2.git"), "github.com/jarx/farmaster2");
+        assert_eq!(normalize_remote("https://github.com/jarx/farmaster2"), "github.com/jarx/farmaster2");
+        assert_eq!(normalize_remote("ssh://git@gitlab.example.com:2222/team/app.git/"), "gitlab.example.com/team/app");
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let output = "1768932000\tOla@example.com\t1768932000\tola@example.com\n\
+                      1768935600\tola@example.com\t1768939200\tci@example.com\n\
+                      1768940000\tbot@example.com\t1768941000\tola@example.com\n\
+                      garbage\n";
+        let times: Vec<String> = parse_log(output, &["ola@example.com".to_string()])
+            .iter()
+            .map(|t| t.format("%H:%M").to_string())
+            .collect();
+        assert_eq!(times, ["18:00", "19:00", "20:30"]);
+    }
+
+    #[test]
+    fn test_worktree_and_subfolder() {
+        let root = TempDir::new("git");
+        let main = root.join("farmaster2");
+        let worktree = root.join("farmaster2-feature");
+        fs::create_dir_all(main.join(".git/worktrees/feature")).unwrap();
+        fs::create_dir_all(main.join("packages/api/src")).unwrap();
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(main.join(".git/config"), "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = git@github.com:jarx/farmaster2.git\n").unwrap();
+        fs::write(main.join(".git/worktrees/feature/commondir"), "../..\n").unwrap();
+        fs::write(worktree.join(".git"), format!("gitdir: {}\n", main.join(".git/worktrees/feature").display())).unwrap();
+
+        let from_subfolder = find_repo(&main.join("packages/api/src/lib.rs")).unwrap();
+        let from_worktree = find_repo(&worktree.join("src/main.rs")).unwrap();
+        assert_eq!(from_subfolder.toplevel, main);
+        assert_eq!((&from_worktree.toplevel, &from_worktree.common_dir), (&from_subfolder.toplevel, &from_subfolder.common_dir));
+        assert_eq!(from_worktree.workdir, worktree);
+        assert_eq!(from_worktree.remote().as_deref(), Some("github.com/jarx/farmaster2"));
+        assert_eq!(find_repo(&std::env::temp_dir().join("after15-no-repo/x")).map(|r| r.toplevel), None);
+    }
+
+    #[test]
+    fn test_configured_repo_outside_roots() {
+        // A repository listed in `sources.git.repos` but under no root.
+        let root = TempDir::new("git-configured");
+        let repo = root.join("side_tool");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("src")).unwrap();
+        let path = repo.to_string_lossy().to_string();
+
+        let projects = ProjectResolver::from_config(&ProjectsConfig::default()).unwrap();
+        assert_eq!(projects.display(&projects.repo_identity(&path).unwrap_or(path.clone())), "Inne");
+
+        let projects = ProjectResolver::from_config(&ProjectsConfig::default())
+            .unwrap()
+            .with_repos(&[repo.join("src").to_string_lossy().to_string()]);
+        let key = projects.repo_identity(&path).unwrap_or(path);
+        assert_eq!(key, repo.to_string_lossy());
+        assert_eq!(projects.display(&key), "side-tool");
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Easter Sunday in the Gregorian calendar (anonymous Gregorian algorithm).
+pub fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+/// Statutory public holidays (dni ustawowo wolne od pracy) in Poland.
+pub fn polish_holidays(year: i32) -> Vec<(NaiveDate, &'static str)> {
+    let fixed = |month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    let easter = easter_sunday(year);
+
+    let mut holidays = vec![
+        (fixed(1, 1), "Nowy Rok"),
+        (easter, "Wielkanoc"),
+        (easter + Duration::days(1), "Poniedziałek Wielkanocny"),
+        (fixed(5, 1), "Święto Pracy"),
+        (fixed(5, 3), "Święto Konstytucji 3 Maja"),
+        (easter + Duration::days(49), "Zielone Świątki"),
+        (easter + Duration::days(60), "Boże Ciało"),
+        (fixed(8, 15), "Wniebowzięcie NMP"),
+        (fixed(11, 1), "Wszystkich Świętych"),
+        (fixed(11, 11), "Narodowe Święto Niepodległości"),
+        (fixed(12, 25), "Boże Narodzenie"),
+        (fixed(12, 26), "Drugi dzień Bożego Narodzenia"),
+    ];
+
+    if year >= 2011 {
+        holidays.push((fixed(1, 6), "Trzech Króli"));
+    }
+    if year >= 2025 {
+        holidays.push((fixed(12, 24), "Wigilia"));
+    }
+
+    holidays.sort_by_key(|(date, _)| *date);
+    holidays
+}
+
+/// Polish holiday lookup that computes each year's list once.
+#[derive(Debug, Clone, Default)]
+pub struct PolishHolidays {
+    years: RefCell<HashMap<i32, Vec<(NaiveDate, &'static str)>>>,
+}
+
+impl PolishHolidays {
+    pub fn name(&self, date: NaiveDate) -> Option<&'static str> {
+        self.years
+            .borrow_mut()
+            .entry(date.year())
+            .or_insert_with(|| polish_holidays(date.year()))
+            .iter()
+            .find(|(d, _)| *d == date)
+            .map(|(_, name)| *name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_easter_sunday() {
+        assert_eq!(easter_sunday(2024), date(2024, 3, 31));
+        assert_eq!(easter_sunday(2025), date(2025, 4, 20));
+        assert_eq!(easter_sunday(2026), date(2026, 4, 5));
+        assert_eq!(easter_sunday(2038), date(2038, 4, 25));
+    }
+
+    #[test]
+    fn test_movable_holidays() {
+        let holidays = PolishHolidays::default();
+        assert_eq!(holidays.name(date(2026, 4, 6)), Some("Poniedziałek Wielkanocny"));
+        assert_eq!(holidays.name(date(2026, 6, 4)), Some("Boże Ciało"));
+        assert_eq!(holidays.name(date(2026, 6, 5)), None);
+    }
+
+    #[test]
+    fn test_fixed_holidays() {
+        let holidays = PolishHolidays::default();
+        assert_eq!(holidays.name(date(2025, 11, 11)), Some("Narodowe Święto Niepodległości"));
+        assert_eq!(holidays.name(date(2025, 12, 24)), Some("Wigilia"));
+        assert_eq!(holidays.name(date(2024, 12, 24)), None);
+        assert_eq!(polish_holidays(2026).len(), 14);
+    }
+}
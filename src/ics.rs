@@ -0,0 +1,287 @@
+use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+/// Start or end of an iCalendar event as written in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IcsTime {
+    /// `VALUE=DATE` - an all-day event.
+    Date(NaiveDate),
+    /// `...Z` - UTC time.
+    Utc(NaiveDateTime),
+    /// Wall-clock time, with the `TZID` parameter if present.
+    Local(NaiveDateTime, Option<String>),
+}
+
+impl IcsTime {
+    /// Date as written, before any time zone conversion.
+    pub fn nominal_date(&self) -> NaiveDate {
+        match self {
+            IcsTime::Date(date) => *date,
+            IcsTime::Utc(dt) | IcsTime::Local(dt, _) => dt.date(),
+        }
+    }
+
+    /// Converts a timed value to wall-clock time in `tz`; `None` for all-day values.
+    pub fn to_local(&self, tz: &Tz) -> Option<NaiveDateTime> {
+        match self {
+            IcsTime::Date(_) => None,
+            IcsTime::Utc(utc) => Some(utc.and_utc().with_timezone(tz).naive_local()),
+            IcsTime::Local(local, None) => Some(*local),
+            IcsTime::Local(local, Some(tzid)) => match tzid.parse::<Tz>() {
+                Ok(event_tz) => event_tz
+                    .from_local_datetime(local)
+                    .earliest()
+                    .map(|dt| dt.with_timezone(tz).naive_local()),
+                Err(_) => Some(*local),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IcsEvent {
+    pub summary: String,
+    pub start: IcsTime,
+    pub end: Option<IcsTime>,
+}
+
+/// Joins folded lines (RFC 5545 3.1): a line starting with a space or tab
+/// continues the previous one.
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        let line = raw.trim_end_matches('\r');
+        if let Some(continuation) = line.strip_prefix(' ').or_else(|| line.strip_prefix('\t'))
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(continuation);
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    lines
+}
+
+/// A content line `NAME;PARAM=x:VALUE`, with name and parameter keys upper-cased.
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+fn split_property(line: &str) -> Option<Property> {
+    let (head, value) = line.split_once(':')?;
+    let mut parts = head.split(';');
+    let name = parts.next()?.to_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => {}
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn parse_time(params: &[(String, String)], value: &str) -> Option<IcsTime> {
+    let is_date = params.iter().any(|(k, v)| k == "VALUE" && v.eq_ignore_ascii_case("DATE"));
+    if is_date || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(IcsTime::Date);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(IcsTime::Utc);
+    }
+
+    let tzid = params.iter().find(|(k, _)| k == "TZID").map(|(_, v)| v.clone());
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|local| IcsTime::Local(local, tzid))
+}
+
+/// Extracts VEVENTs from an iCalendar file. Events without a DTSTART are skipped.
+pub fn parse_events(content: &str) -> Vec<IcsEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<(String, Option<IcsTime>, Option<IcsTime>)> = None;
+
+    for line in unfold(content) {
+        let Some(Property { name, params, value }) = split_property(&line) else {
+            continue;
+        };
+
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some((String::new(), None, None));
+            }
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                if let Some((summary, Some(start), end)) = current.take() {
+                    events.push(IcsEvent { summary, start, end });
+                }
+            }
+            "SUMMARY" => {
+                if let Some(event) = current.as_mut() {
+                    event.0 = unescape_text(&value);
+                }
+            }
+            "DTSTART" => {
+                if let Some(event) = current.as_mut() {
+                    event.1 = parse_time(&params, &value);
+                }
+            }
+            "DTEND" => {
+                if let Some(event) = current.as_mut() {
+                    event.2 = parse_time(&params, &value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// An event to be written out; times are UTC.
+pub struct OutputEvent {
+    pub uid: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub summary: String,
+    pub description: String,
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line to at most 75 octets per line (RFC 5545 3.1),
+/// never splitting a UTF-8 character.
+fn fold(line: &str) -> String {
+    let mut result = String::with_capacity(line.len() + 8);
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            result.push_str("\r\n ");
+            line_len = 1;
+        }
+        result.push(c);
+        line_len += c.len_utf8();
+    }
+    result.push_str("\r\n");
+    result
+}
+
+pub fn write_calendar(name: &str, events: &[OutputEvent], stamp: NaiveDateTime) -> String {
+    let utc = |t: &NaiveDateTime| t.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut out = String::new();
+    out.push_str(&fold("BEGIN:VCALENDAR"));
+    out.push_str(&fold("VERSION:2.0"));
+    out.push_str(&fold("PRODID:-//after15//Nadgodziny//PL"));
+    out.push_str(&fold(&format!("X-WR-CALNAME:{}", escape_text(name))));
+
+    for event in events {
+        out.push_str(&fold("BEGIN:VEVENT"));
+        out.push_str(&fold(&format!("UID:{}", event.uid)));
+        out.push_str(&fold(&format!("DTSTAMP:{}", utc(&stamp))));
+        out.push_str(&fold(&format!("DTSTART:{}", utc(&event.start))));
+        out.push_str(&fold(&format!("DTEND:{}", utc(&event.end))));
+        out.push_str(&fold(&format!("SUMMARY:{}", escape_text(&event.summary))));
+        out.push_str(&fold(&format!("DESCRIPTION:{}", escape_text(&event.description))));
+        out.push_str(&fold("END:VEVENT"));
+    }
+
+    out.push_str(&fold("END:VCALENDAR"));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROTA: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Zmiana nocna\\, hala 2\r\n\
+DTSTART;TZID=Europe/Warsaw:20260105T220000\r\n\
+DTEND;TZID=Europe/Warsaw:20260106T060000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Zmiana\r\n  ranna\r\n\
+DTSTART:20260107T050000Z\r\n\
+DTEND:20260107T140000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Wolne\r\n\
+DTSTART;VALUE=DATE:20260108\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_events() {
+        let events = parse_events(ROTA);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].summary, "Zmiana nocna, hala 2");
+        assert_eq!(events[1].summary, "Zmiana ranna");
+        assert_eq!(
+            events[2].start,
+            IcsTime::Date(NaiveDate::from_ymd_opt(2026, 1, 8).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_to_local() {
+        let events = parse_events(ROTA);
+        let warsaw = chrono_tz::Europe::Warsaw;
+
+        let night_start = events[0].start.to_local(&warsaw).unwrap();
+        assert_eq!(night_start.to_string(), "2026-01-05 22:00:00");
+
+        let morning_start = events[1].start.to_local(&warsaw).unwrap();
+        assert_eq!(morning_start.to_string(), "2026-01-07 06:00:00");
+
+        assert!(events[2].start.to_local(&warsaw).is_none());
+    }
+
+    #[test]
+    fn test_write_calendar_round_trip() {
+        let at = |h| NaiveDate::from_ymd_opt(2026, 1, 13).unwrap().and_hms_opt(h, 0, 0).unwrap();
+        let events = vec![OutputEvent {
+            uid: "a@after15".to_string(),
+            start: at(16),
+            end: at(18),
+            summary: "Nadgodziny: farmaster2, after15-core (120 min)".to_string(),
+            description: "Linia 1\nLinia 2; ".repeat(10),
+        }];
+
+        let calendar = write_calendar("Nadgodziny", &events, at(20));
+        assert!(calendar.lines().all(|l| l.trim_end_matches('\r').len() <= 75));
+
+        let parsed = parse_events(&calendar);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].summary, events[0].summary);
+        assert_eq!(parsed[0].start, IcsTime::Utc(at(16)));
+        assert_eq!(parsed[0].end, Some(IcsTime::Utc(at(18))));
+    }
+}
@@ -1,13 +1,18 @@
-use chrono::{NaiveDate, NaiveDateTime, Local};
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::Regex;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-use crate::overtime::calculate_session_overtime;
-use crate::schedule::is_weekend;
+use crate::config::{ActivityConfig, Attribution, Config, LogSourceConfig, SubagentPolicy};
+use crate::overtime::{calculate_session_overtime, day_blocks, overtime_blocks};
+use crate::projects::{encode_project_dir, glob_to_regex, ProjectResolver};
+use crate::schedule::Schedule;
+use crate::sources::{ActivitySource, Scope, Sources};
 
 #[derive(Debug, Clone)]
 pub struct Session {
@@ -16,18 +21,355 @@ pub struct Session {
     #[allow(dead_code)]
     pub project: String,
     pub project_counts: HashMap<String, usize>,
+    /// Where the project changes, in time order. Active time from a mark to
+    /// the next belongs to the mark's project, like the pause after a record
+    /// is credited by that record; padding before the first mark belongs to it.
+    pub project_marks: Vec<(NaiveDateTime, String)>,
+    /// Paths touched by tools (`file_path`, `path`, `workdir`), with the
+    /// number of records that touched each.
+    pub path_counts: HashMap<String, usize>,
     pub start_time: NaiveDateTime,
+    /// Active parts of the session, sorted and disjoint; idle time between
+    /// them is not counted. Overtime is computed from these.
+    pub segments: Vec<(NaiveDateTime, NaiveDateTime)>,
+    /// End including the estimated part; overtime is counted up to here.
     pub end_time: NaiveDateTime,
+    /// Measured active time (the segments without padding).
     pub duration_seconds: i64,
+    /// Padding added on top of the measured span (see `Padding`).
+    pub estimated_seconds: i64,
+    /// Thresholds of the dominant project, used for the minimum-length check.
+    pub thresholds: Thresholds,
+    /// Sub-agent events linked to the session through their parent log
+    /// session, whether counted or not; for agent-driven sessions, the events
+    /// left after cutting out the main sessions.
+    pub subagent_events: usize,
+    /// Built from sub-agent logs only (`SubagentPolicy::Separate`).
+    pub agent_driven: bool,
+    pub stats: SessionStats,
+}
+
+/// What the session's time went on, from the records' details.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionStats {
+    pub prompts: usize,
+    pub tool_calls: usize,
+    /// Tool name -> number of calls.
+    pub tools: HashMap<String, usize>,
+    pub models: BTreeSet<String>,
+    pub branches: BTreeSet<String>,
+    /// Activity source -> number of records in the session.
+    pub sources: BTreeMap<&'static str, usize>,
+}
+
+impl SessionStats {
+    fn add(&mut self, record: &TimestampRecord) {
+        let details = &record.details;
+        if !record.source.is_empty() {
+            *self.sources.entry(record.source).or_insert(0) += 1;
+        }
+        if record.kind == EventKind::Prompt {
+            self.prompts += 1;
+        }
+        self.tool_calls += details.tools.len();
+        for tool in &details.tools {
+            *self.tools.entry(tool.clone()).or_insert(0) += 1;
+        }
+        self.models.extend(details.model.iter().cloned());
+        self.branches.extend(details.branch.iter().cloned());
+    }
+}
+
+impl Session {
+    /// Weight of each project in the session ("transcripts" excluded); divide
+    /// by the sum to get shares. By time, a project weighs its active seconds
+    /// inside `intervals` (parts of the segments, e.g. the overtime of one
+    /// day); when there are none, records are counted instead.
+    pub fn project_weights(&self, attribution: Attribution, intervals: &[(NaiveDateTime, NaiveDateTime)]) -> HashMap<String, f64> {
+        if attribution == Attribution::Time {
+            let by_time = self.project_seconds_within(intervals);
+            if !by_time.is_empty() {
+                return by_time;
+            }
+        }
+        
+        self.project_counts
+            .iter()
+            .filter(|(name, _)| *name != "transcripts")
+            .map(|(name, count)| (name.clone(), *count as f64))
+            .collect()
+    }
+    
+    fn project_seconds_within(&self, intervals: &[(NaiveDateTime, NaiveDateTime)]) -> HashMap<String, f64> {
+        let marks = &self.project_marks;
+        let mut seconds: HashMap<String, f64> = HashMap::new();
+        
+        for &(start, end) in intervals {
+            let mut i = marks.partition_point(|(t, _)| *t <= start).saturating_sub(1);
+            let mut cursor = start;
+            while cursor < end && i < marks.len() {
+                let next = marks.get(i + 1).map(|(t, _)| *t).filter(|t| *t < end).unwrap_or(end);
+                *seconds.entry(marks[i].1.clone()).or_insert(0.0) += (next - cursor).num_milliseconds() as f64 / 1000.0;
+                cursor = next;
+                i += 1;
+            }
+        }
+        
+        seconds.retain(|name, secs| name != "transcripts" && *secs > 0.0);
+        seconds
+    }
+}
+
+#[cfg(test)]
+impl Session {
+    /// One active segment from `start` to `end` and nothing else; tests
+    /// override the fields they need.
+    pub fn spanning(start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        Self {
+            id: "test".to_string(),
+            project: String::new(),
+            project_counts: HashMap::new(),
+            project_marks: Vec::new(),
+            path_counts: HashMap::new(),
+            start_time: start,
+            segments: vec![(start, end)],
+            end_time: end,
+            duration_seconds: (end - start).num_seconds(),
+            estimated_seconds: 0,
+            thresholds: SessionRules::default().default,
+            subagent_events: 0,
+            agent_driven: false,
+            stats: SessionStats::default(),
+        }
+    }
+}
+
+/// Project key under which separate sub-agent time is reported.
+pub const AGENT_PROJECT: &str = "agent-driven";
+
+/// Limits used to cut the record stream into sessions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub gap_seconds: i64,
+    pub min_session_seconds: i64,
+}
+
+/// Time not visible in the logs: thinking before the first event, reading
+/// after the last one, and a floor per active burst so that a single isolated
+/// prompt does not count as zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Padding {
+    pub before_seconds: i64,
+    pub after_seconds: i64,
+    pub min_burst_seconds: i64,
+}
+
+/// Share of the pause after an event that is credited as work.
+#[derive(Debug, Clone)]
+pub struct ActivityWeights {
+    prompt: f64,
+    tool: f64,
+    assistant: f64,
+    assistant_cap_seconds: Option<i64>,
+}
+
+impl ActivityWeights {
+    fn from_config(config: &ActivityConfig) -> Self {
+        Self {
+            prompt: config.prompt_weight.clamp(0.0, 1.0),
+            tool: config.tool_weight.clamp(0.0, 1.0),
+            assistant: config.assistant_weight.clamp(0.0, 1.0),
+            assistant_cap_seconds: config.assistant_cap_minutes.map(|m| m as i64 * 60),
+        }
+    }
+
+    /// Scaled by the weight of the record's source as well.
+    fn credit(&self, after: &TimestampRecord, gap_seconds: i64) -> i64 {
+        let weight = match after.kind {
+            EventKind::Prompt => self.prompt,
+            EventKind::Tool => self.tool,
+            EventKind::Assistant => self.assistant,
+            EventKind::Other => 1.0,
+        };
+        let credited = (gap_seconds as f64 * weight * after.weight).round() as i64;
+        match (after.kind, self.assistant_cap_seconds) {
+            (EventKind::Assistant, Some(cap)) => credited.min(cap),
+            _ => credited,
+        }
+    }
+}
+
+/// Session thresholds: the global ones from config (or CLI) and per-project overrides.
+#[derive(Debug, Clone)]
+pub struct SessionRules {
+    pub default: Thresholds,
+    pub padding: Padding,
+    activity: ActivityWeights,
+    pub subagents: SubagentPolicy,
+    pub attribution: Attribution,
+    pub projects: ProjectResolver,
+    project_thresholds: HashMap<String, Thresholds>,
+}
+
+impl SessionRules {
+    /// CLI values replace the config ones for every project, overrides included.
+    pub fn from_config(config: &Config, gap_minutes: Option<u32>, min_session_minutes: Option<u32>) -> Result<Self, String> {
+        let sessions = &config.sessions;
+        let default = Thresholds {
+            gap_seconds: gap_minutes.unwrap_or(sessions.gap_minutes) as i64 * 60,
+            min_session_seconds: min_session_minutes.unwrap_or(sessions.min_session_minutes) as i64 * 60,
+        };
+
+        let project_thresholds = sessions
+            .projects
+            .iter()
+            .map(|(name, o)| {
+                let gap = gap_minutes.or(o.gap_minutes).map(|m| m as i64 * 60);
+                let min = min_session_minutes.or(o.min_session_minutes).map(|m| m as i64 * 60);
+                (name.clone(), Thresholds {
+                    gap_seconds: gap.unwrap_or(default.gap_seconds),
+                    min_session_seconds: min.unwrap_or(default.min_session_seconds),
+                })
+            })
+            .collect();
+        let git = &config.sources.git;
+        let git_repos: &[String] = if git.enabled { &git.repos } else { &[] };
+
+        Ok(Self {
+            default,
+            padding: Padding {
+                before_seconds: sessions.padding_before_minutes as i64 * 60,
+                after_seconds: sessions.padding_after_minutes as i64 * 60,
+                min_burst_seconds: sessions.min_burst_minutes as i64 * 60,
+            },
+            activity: ActivityWeights::from_config(&sessions.activity),
+            subagents: sessions.subagents,
+            attribution: config.projects.attribution,
+            projects: ProjectResolver::from_config(&config.projects)?.with_repos(git_repos),
+            project_thresholds,
+        })
+    }
+
+    pub fn for_project(&self, raw_project: &str) -> Thresholds {
+        self.project_thresholds
+            .get(raw_project)
+            .or_else(|| self.project_thresholds.get(&self.projects.display(raw_project)))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+impl Default for SessionRules {
+    fn default() -> Self {
+        Self::from_config(&Config::default(), None, None).unwrap()
+    }
 }
 
 #[derive(Deserialize)]
 struct JsonlEntry {
+    uuid: Option<String>,
     timestamp: Option<String>,
     #[serde(rename = "sessionId")]
-    #[allow(dead_code)]
     session_id: Option<String>,
     tool_input: Option<ToolInput>,
+    #[serde(rename = "type")]
+    entry_type: Option<String>,
+    cwd: Option<String>,
+    #[serde(rename = "gitBranch")]
+    git_branch: Option<String>,
+    message: Option<Message>,
+}
+
+/// Fields of `message` the calculator uses; everything else is ignored.
+#[derive(Deserialize)]
+struct Message {
+    role: Option<String>,
+    model: Option<String>,
+    #[serde(default)]
+    content: serde_json::Value,
+}
+
+/// Per-record details kept for session statistics.
+#[derive(Debug, Clone, Default)]
+pub struct EventDetails {
+    /// Names of the tools called in the record (one per `tool_use` block).
+    pub tools: Vec<String>,
+    pub model: Option<String>,
+    pub branch: Option<String>,
+}
+
+/// What a log entry was, as far as activity is concerned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventKind {
+    Prompt,
+    Tool,
+    Assistant,
+    Other,
+}
+
+impl JsonlEntry {
+    fn content_blocks(&self, block_type: &str) -> impl Iterator<Item = &serde_json::Value> {
+        self.message
+            .as_ref()
+            .and_then(|m| m.content.as_array())
+            .into_iter()
+            .flatten()
+            .filter(move |b| b["type"] == block_type)
+    }
+
+    fn has_content_block(&self, block_type: &str) -> bool {
+        self.content_blocks(block_type).next().is_some()
+    }
+
+    fn details(&self) -> EventDetails {
+        let message = self.message.as_ref();
+        EventDetails {
+            tools: self
+                .content_blocks("tool_use")
+                .filter_map(|b| b["name"].as_str().map(str::to_string))
+                .collect(),
+            // Claude Code marks its own canned replies with a "<synthetic>" model.
+            model: message
+                .and_then(|m| m.model.clone())
+                .filter(|m| !m.starts_with('<')),
+            // Detached HEAD is logged as "HEAD", which names no branch.
+            branch: self
+                .git_branch
+                .clone()
+                .filter(|b| !b.is_empty() && b != "HEAD"),
+        }
+    }
+
+    /// Path a tool worked on: the transcript `tool_input`, else the input of
+    /// the first `tool_use` block of the message.
+    fn tool_path(&self) -> Option<String> {
+        if let Some(tool_input) = &self.tool_input {
+            return tool_input.file_path.as_ref()
+                .or(tool_input.path.as_ref())
+                .or(tool_input.workdir.as_ref())
+                .cloned();
+        }
+        self.content_blocks("tool_use")
+            .find_map(|b| {
+                let input = &b["input"];
+                input["file_path"].as_str()
+                    .or(input["notebook_path"].as_str())
+                    .or(input["path"].as_str())
+                    .map(str::to_string)
+            })
+    }
+
+    fn event_kind(&self) -> EventKind {
+        let role = self.message.as_ref().and_then(|m| m.role.as_deref());
+        match self.entry_type.as_deref().or(role) {
+            Some("user") if self.has_content_block("tool_result") => EventKind::Tool,
+            Some("user") => EventKind::Prompt,
+            Some("assistant") if self.has_content_block("tool_use") => EventKind::Tool,
+            Some("assistant") => EventKind::Assistant,
+            Some("tool_use") | Some("tool_result") => EventKind::Tool,
+            _ => EventKind::Other,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -93,25 +435,25 @@ pub fn load_daily_summary_full(debug: bool) -> DailySummaryData {
         return result;
     }
     
-    if let Ok(content) = fs::read_to_string(&path) {
-        if let Ok(summary) = serde_json::from_str::<DailySummary>(&content) {
-            for (date_str, day_data) in summary.days {
-                if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
-                    if day_data.hours > 0.0 {
-                        result.hours.insert(date, day_data.hours);
+    if let Ok(content) = fs::read_to_string(&path)
+        && let Ok(summary) = serde_json::from_str::<DailySummary>(&content)
+    {
+        for (date_str, day_data) in summary.days {
+            if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
+                if day_data.hours > 0.0 {
+                    result.hours.insert(date, day_data.hours);
+                }
+                
+                if let Some(projects) = day_data.projects {
+                    let mut day_projects: HashMap<String, ProjectHours> = HashMap::new();
+                    for (proj_name, proj_hours) in projects {
+                        day_projects.insert(proj_name, ProjectHours {
+                            weekday_hours: proj_hours.weekday_hours,
+                            weekend_hours: proj_hours.weekend_hours,
+                        });
                     }
-                    
-                    if let Some(projects) = day_data.projects {
-                        let mut day_projects: HashMap<String, ProjectHours> = HashMap::new();
-                        for (proj_name, proj_hours) in projects {
-                            day_projects.insert(proj_name, ProjectHours {
-                                weekday_hours: proj_hours.weekday_hours,
-                                weekend_hours: proj_hours.weekend_hours,
-                            });
-                        }
-                        if !day_projects.is_empty() {
-                            result.projects.insert(date, day_projects);
-                        }
+                    if !day_projects.is_empty() {
+                        result.projects.insert(date, day_projects);
                     }
                 }
             }
@@ -126,35 +468,20 @@ pub fn load_daily_summary_full(debug: bool) -> DailySummaryData {
     result
 }
 
-pub fn find_today_jsonl_files(debug: bool) -> Vec<PathBuf> {
-    find_jsonl_files(Some(Local::now().date_naive()), None, debug)
-}
-
-pub fn find_recent_jsonl_files(days: i64, debug: bool) -> Vec<PathBuf> {
-    let cutoff = Local::now().date_naive() - chrono::Duration::days(days);
-    find_jsonl_files(None, Some(cutoff), debug)
-}
-
-pub fn find_all_jsonl_files(debug: bool) -> Vec<PathBuf> {
-    find_jsonl_files(None, None, debug)
+/// Whether a file was modified within `scope`; unreadable times count as in.
+pub fn modified_in(path: &Path, scope: Scope) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|modified| scope.includes(chrono::DateTime::<chrono::Utc>::from(modified).naive_utc()))
+        .unwrap_or(true)
 }
 
-fn find_jsonl_files(date_filter: Option<NaiveDate>, min_date: Option<NaiveDate>, debug: bool) -> Vec<PathBuf> {
+/// Files with `extension` below `search_dirs`, skipping those not modified
+/// within `scope`.
+pub fn find_log_files(search_dirs: &[PathBuf], extension: &str, scope: Scope, debug: bool) -> Vec<PathBuf> {
     let mut files = Vec::new();
     
-    let claude_dir = dirs::home_dir()
-        .map(|p| p.join(".claude"));
-    
-    let Some(claude_path) = claude_dir else {
-        return files;
-    };
-    
-    let search_dirs = [
-        claude_path.join("projects"),
-        claude_path.join("transcripts"),
-    ];
-    
-    for search_dir in &search_dirs {
+    for search_dir in search_dirs {
         if !search_dir.exists() {
             continue;
         }
@@ -170,36 +497,17 @@ fn find_jsonl_files(date_filter: Option<NaiveDate>, min_date: Option<NaiveDate>,
                 continue;
             }
             
-            if path.extension().map(|e| e != "jsonl").unwrap_or(true) {
+            if path.extension().map(|e| e != extension).unwrap_or(true) {
                 continue;
             }
             
-            let path_str = path.to_string_lossy();
-            if path_str.contains("/subagents/") {
+            if !modified_in(path, scope) {
                 continue;
             }
             
-            if let Ok(metadata) = fs::metadata(path) {
-                if let Ok(modified) = metadata.modified() {
-                    let modified_date = chrono::DateTime::<Local>::from(modified).date_naive();
-                    
-                    if let Some(filter_date) = date_filter {
-                        if modified_date != filter_date {
-                            continue;
-                        }
-                    }
-                    
-                    if let Some(cutoff) = min_date {
-                        if modified_date < cutoff {
-                            continue;
-                        }
-                    }
-                }
-            }
-            
             files.push(path.to_path_buf());
             if debug {
-                eprintln!("[DEBUG] Found JSONL: {:?}", path);
+                eprintln!("[DEBUG] Found log: {:?}", path);
             }
         }
     }
@@ -207,108 +515,206 @@ fn find_jsonl_files(date_filter: Option<NaiveDate>, min_date: Option<NaiveDate>,
     files
 }
 
+/// Where a log source looks: its search roots and `include`/`exclude` globs.
+/// A glob containing `/` is matched against the whole path, one without
+/// against the file name.
+pub struct LogFiles {
+    pub dirs: Vec<PathBuf>,
+    include: Vec<(Regex, bool)>,
+    exclude: Vec<(Regex, bool)>,
+}
+
+impl LogFiles {
+    pub fn from_config(config: &LogSourceConfig, default_dirs: &[&str]) -> Self {
+        let compile = |globs: &[String]| -> Vec<(Regex, bool)> {
+            globs
+                .iter()
+                .filter_map(|glob| Some((Regex::new(&glob_to_regex(glob)).ok()?, glob.contains('/'))))
+                .collect()
+        };
+        Self {
+            dirs: config.search_dirs(default_dirs),
+            include: compile(&config.include),
+            exclude: compile(&config.exclude),
+        }
+    }
+
+    pub fn is_wanted(&self, path: &Path) -> bool {
+        let full = path.to_string_lossy();
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        let matches = |(regex, whole_path): &(Regex, bool)| regex.is_match(if *whole_path { &full } else { &name });
+        (self.include.is_empty() || self.include.iter().any(matches)) && !self.exclude.iter().any(matches)
+    }
+
+    /// Wanted files with `extension` below the search roots.
+    pub fn find(&self, extension: &str, scope: Scope, debug: bool) -> Vec<PathBuf> {
+        find_log_files(&self.dirs, extension, scope, debug)
+            .into_iter()
+            .filter(|path| self.is_wanted(path))
+            .collect()
+    }
+
+    /// `path` below the deepest search root holding it, so copies of a log
+    /// synced into different roots share it.
+    pub fn relative(&self, path: &Path) -> PathBuf {
+        self.dirs
+            .iter()
+            .filter_map(|dir| path.strip_prefix(dir).ok())
+            .min_by_key(|rest| rest.components().count())
+            .unwrap_or(path)
+            .to_path_buf()
+    }
+}
+
+/// Records seen so far, by a key naming the record, with the file that
+/// produced them. The same log synced into several roots (e.g. from a second
+/// laptop) repeats its records; only the first file counts them. Repeats
+/// within one file (blocks of one message sharing a timestamp) are kept.
+#[derive(Default)]
+pub struct SeenRecords {
+    seen: HashMap<String, usize>,
+    file: usize,
+}
+
+impl SeenRecords {
+    /// Starts counting records of the next file.
+    pub fn next_file(&mut self) {
+        self.file += 1;
+    }
+
+    pub fn is_new(&mut self, key: String) -> bool {
+        let file = self.file;
+        *self.seen.entry(key).or_insert(file) == file
+    }
+}
+
 pub struct TodayData {
     pub hours: HashMap<NaiveDate, f64>,
     pub projects: HashMap<NaiveDate, HashMap<String, ProjectHours>>,
 }
 
-pub fn load_today_overtime(debug: bool) -> TodayData {
-    load_overtime_from_files(find_today_jsonl_files(debug), Some(Local::now().date_naive()), debug)
-}
-
-pub fn load_recent_overtime(days: i64, debug: bool) -> TodayData {
-    load_overtime_from_files(find_recent_jsonl_files(days, debug), None, debug)
+pub fn load_recent_overtime(days: i64, sources: &Sources, schedule: &Schedule, rules: &SessionRules, debug: bool) -> TodayData {
+    let cutoff = schedule.today() - chrono::Duration::days(days);
+    let records = sources.collect(Scope::since_date(cutoff, schedule.zones()), &rules.projects, debug);
+    load_overtime_from_records(records, schedule, rules, None, debug)
 }
 
-pub fn load_all_overtime(debug: bool) -> TodayData {
-    load_overtime_from_files(find_all_jsonl_files(debug), None, debug)
+pub fn load_sessions_for_date(date: NaiveDate, sources: &Sources, schedule: &Schedule, rules: &SessionRules, debug: bool) -> Vec<Session> {
+    load_sessions_for_range(date, date, sources, schedule, rules, debug)
 }
 
-pub fn load_sessions_for_date(date: NaiveDate, debug: bool) -> Vec<Session> {
-    use chrono_tz::Europe::Warsaw;
-    
-    let files = find_all_jsonl_files(debug);
-    
-    let mut all_records: Vec<TimestampRecord> = Vec::new();
-    
-    for file_path in &files {
-        let records = collect_timestamps_from_file(file_path);
-        all_records.extend(records);
-    }
+/// Sessions touching any local date in `from..=to`.
+pub fn load_sessions_for_range(from: NaiveDate, to: NaiveDate, sources: &Sources, schedule: &Schedule, rules: &SessionRules, debug: bool) -> Vec<Session> {
+    let all_records = sources.collect(Scope::All, &rules.projects, debug);
     
     if all_records.is_empty() {
         return Vec::new();
     }
     
-    all_records.sort_by_key(|r| r.timestamp);
-    
-    let sessions = build_sessions_from_records(&all_records, false);
+    let sessions = build_sessions(&all_records, rules, false);
     
     sessions
         .into_iter()
         .filter(|s| {
-            let start_local = s.start_time.and_utc().with_timezone(&Warsaw).naive_local();
-            let end_local = s.end_time.and_utc().with_timezone(&Warsaw).naive_local();
-            let start_date = start_local.date();
-            let end_date = end_local.date();
-            date >= start_date && date <= end_date
+            let start_date = schedule.to_local(s.start_time).date();
+            let end_date = schedule.to_local(s.end_time).date();
+            start_date <= to && end_date >= from
         })
         .collect()
 }
 
-const SESSION_GAP_SECONDS: i64 = 30 * 60;
-const MIN_SESSION_SECONDS: i64 = 5 * 60;
-
+/// One timestamped piece of activity, from any source.
 #[derive(Debug, Clone)]
-struct TimestampRecord {
-    timestamp: NaiveDateTime,
-    project: String,
+pub struct TimestampRecord {
+    pub timestamp: NaiveDateTime,
+    /// Project key: a path, a repository identity or a resolved name.
+    pub project: String,
+    pub kind: EventKind,
+    pub subagent: bool,
+    /// ID of the log session the record comes from; for sub-agent records,
+    /// the parent session's.
+    pub session: Option<String>,
+    pub path: Option<String>,
+    pub details: EventDetails,
+    /// Name of the source (see `ActivitySource::name`).
+    pub source: &'static str,
+    /// Share of the pause after the record that counts, set from config.
+    pub weight: f64,
+}
+
+impl TimestampRecord {
+    pub fn new(timestamp: NaiveDateTime, project: String, kind: EventKind) -> Self {
+        Self {
+            timestamp,
+            project,
+            kind,
+            subagent: false,
+            session: None,
+            path: None,
+            details: EventDetails::default(),
+            source: "",
+            weight: 1.0,
+        }
+    }
+}
+
+/// Claude Code logs, by default in `~/.claude/projects` and `~/.claude/transcripts`.
+/// Sub-agent logs are always read; `SubagentPolicy` only decides how they count.
+pub struct ClaudeSource {
+    files: LogFiles,
 }
 
-fn load_overtime_from_files(files: Vec<PathBuf>, date_filter: Option<NaiveDate>, debug: bool) -> TodayData {
+impl ClaudeSource {
+    pub fn from_config(config: &LogSourceConfig) -> Self {
+        Self { files: LogFiles::from_config(config, &["~/.claude/projects", "~/.claude/transcripts"]) }
+    }
+}
+
+impl ActivitySource for ClaudeSource {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    fn records(&self, scope: Scope, projects: &ProjectResolver, debug: bool) -> Vec<TimestampRecord> {
+        let files = self.files.find("jsonl", scope, debug);
+        if debug {
+            eprintln!("[DEBUG] Processing {} JSONL files", files.len());
+        }
+        let mut seen = SeenRecords::default();
+        files
+            .iter()
+            .flat_map(|path| {
+                seen.next_file();
+                collect_timestamps_from_file(path, &self.files.relative(path), projects, &mut seen)
+            })
+            .collect()
+    }
+}
+
+/// Records must be sorted by time (`Sources::collect` does that).
+fn load_overtime_from_records(all_records: Vec<TimestampRecord>, schedule: &Schedule, rules: &SessionRules, date_filter: Option<NaiveDate>, debug: bool) -> TodayData {
     let mut result = TodayData {
         hours: HashMap::new(),
         projects: HashMap::new(),
     };
     
-    if debug {
-        eprintln!("[DEBUG] Processing {} JSONL files with GLOBAL gap detection", files.len());
-    }
-    
-    let mut all_records: Vec<TimestampRecord> = Vec::new();
-    
-    for file_path in &files {
-        let records = collect_timestamps_from_file(file_path);
-        all_records.extend(records);
-    }
-    
     if all_records.is_empty() {
         return result;
     }
     
-    all_records.sort_by_key(|r| r.timestamp);
-    
     if debug {
         eprintln!("[DEBUG] Collected {} total records from all files", all_records.len());
     }
     
-    let sessions = build_sessions_from_records(&all_records, debug);
+    let sessions = build_sessions(&all_records, rules, debug);
     
     if debug {
         eprintln!("[DEBUG] Created {} sessions from global gap detection", sessions.len());
     }
     
     for session in sessions {
-        let filter = date_filter.unwrap_or(session.start_time.date());
-        let overtime = calculate_session_overtime(&session, filter, debug);
-        
-        let real_projects: HashMap<String, usize> = session.project_counts
-            .iter()
-            .filter(|(name, _)| *name != "transcripts")
-            .map(|(k, v)| (k.clone(), *v))
-            .collect();
-        
-        let total_records: usize = real_projects.values().sum();
+        let overtime = calculate_session_overtime(&session, schedule, debug);
+        let blocks = overtime_blocks(&session, schedule);
         
         for (date, hours) in overtime {
             let dominated = date_filter.map(|f| date != f).unwrap_or(false);
@@ -316,25 +722,32 @@ fn load_overtime_from_files(files: Vec<PathBuf>, date_filter: Option<NaiveDate>,
                 continue;
             }
             
+            let real_projects: HashMap<String, f64> = if session.agent_driven {
+                HashMap::from([(AGENT_PROJECT.to_string(), 1.0)])
+            } else {
+                session.project_weights(rules.attribution, &day_blocks(&blocks, date))
+            };
+            let total_weight: f64 = real_projects.values().sum();
+            
             *result.hours.entry(date).or_insert(0.0) += hours;
             
             let day_projects = result.projects.entry(date).or_default();
             
-            if total_records == 0 {
+            if total_weight <= 0.0 {
                 let proj_entry = day_projects.entry("unknown".to_string()).or_default();
-                if is_weekend(date) {
+                if schedule.is_weekend_rate(date) {
                     proj_entry.weekend_hours += hours;
                 } else {
                     proj_entry.weekday_hours += hours;
                 }
             } else {
-                for (proj_name, &count) in &real_projects {
-                    let fraction = count as f64 / total_records as f64;
+                for (proj_name, &weight) in &real_projects {
+                    let fraction = weight / total_weight;
                     let proj_hours = hours * fraction;
                     
                     let proj_entry = day_projects.entry(proj_name.clone()).or_default();
                     
-                    if is_weekend(date) {
+                    if schedule.is_weekend_rate(date) {
                         proj_entry.weekend_hours += proj_hours;
                     } else {
                         proj_entry.weekday_hours += proj_hours;
@@ -347,7 +760,9 @@ fn load_overtime_from_files(files: Vec<PathBuf>, date_filter: Option<NaiveDate>,
     result
 }
 
-fn collect_timestamps_from_file(path: &Path) -> Vec<TimestampRecord> {
+/// Records of one log; `relative` is its path below the search root, which
+/// names records without a `uuid` for deduplication.
+fn collect_timestamps_from_file(path: &Path, relative: &Path, projects: &ProjectResolver, seen: &mut SeenRecords) -> Vec<TimestampRecord> {
     let mut records = Vec::new();
     
     let file = match File::open(path) {
@@ -355,133 +770,333 @@ fn collect_timestamps_from_file(path: &Path) -> Vec<TimestampRecord> {
         Err(_) => return records,
     };
     let reader = BufReader::new(file);
-    let default_project = extract_project_name(path);
-    let is_transcript = default_project == "transcripts";
-    
-    for line in reader.lines().flatten() {
-        if let Ok(entry) = serde_json::from_str::<JsonlEntry>(&line) {
-            if let Some(ref ts_str) = entry.timestamp {
-                if let Some(ts) = parse_timestamp(ts_str) {
-                    let project = if is_transcript {
-                        extract_project_from_tool_input(&entry).unwrap_or_else(|| default_project.clone())
-                    } else {
-                        default_project.clone()
-                    };
-                    
-                    records.push(TimestampRecord {
-                        timestamp: ts,
-                        project,
-                    });
-                }
+    let dir_name = extract_project_name(path);
+    let is_transcript = dir_name == "transcripts";
+    let subagent = is_subagent_log(path);
+    let file_stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    // `<session>/subagents/agent-*.jsonl` belongs to `<session>.jsonl`.
+    let parent_session = subagent
+        .then(|| path.parent()?.parent()?.file_name())
+        .flatten()
+        .map(|name| name.to_string_lossy().to_string());
+    let mut cwd: Option<String> = None;
+    
+    for line in reader.lines().map_while(Result::ok) {
+        if let Ok(entry) = serde_json::from_str::<JsonlEntry>(&line)
+            && let Some(ref ts_str) = entry.timestamp
+            && let Some(ts) = parse_timestamp(ts_str)
+            && seen.is_new(entry.uuid.clone().unwrap_or_else(|| {
+                format!("{}|{}|{}", relative.display(), entry.session_id.as_deref().unwrap_or(&file_stem), ts_str)
+            }))
+        {
+            // The directory is named after the session's starting cwd; a later
+            // `cd` must not re-key the whole file.
+            if let Some(entry_cwd) = &entry.cwd
+                && cwd.as_deref().is_none_or(|c| encode_project_dir(c) != dir_name)
+            {
+                cwd = Some(entry_cwd.clone());
             }
+            
+            let path = entry.tool_path();
+            let project = if is_transcript {
+                path.as_deref()
+                    .and_then(|p| projects.key_for_path(p))
+                    .unwrap_or_else(|| dir_name.clone())
+            } else {
+                String::new()
+            };
+            
+            let session = parent_session.clone()
+                .or_else(|| entry.session_id.clone())
+                .unwrap_or_else(|| file_stem.clone());
+            records.push(TimestampRecord {
+                subagent,
+                session: Some(session),
+                path,
+                details: entry.details(),
+                ..TimestampRecord::new(ts, project, entry.event_kind())
+            });
+        }
+    }
+    
+    if !is_transcript {
+        let project = project_dir_path(&dir_name, cwd, projects);
+        let project = projects.repo_identity(&project).unwrap_or(project);
+        for record in &mut records {
+            record.project = project.clone();
         }
     }
     
     records
 }
 
-fn extract_project_from_tool_input(entry: &JsonlEntry) -> Option<String> {
-    let tool_input = entry.tool_input.as_ref()?;
+/// Real path behind a `~/.claude/projects` directory name: the cwd recorded
+/// in the log, else a filesystem probe, else the encoded name itself.
+fn project_dir_path(dir_name: &str, cwd: Option<String>, projects: &ProjectResolver) -> String {
+    if !dir_name.starts_with('-') {
+        return dir_name.to_string();
+    }
+    cwd.or_else(|| projects.probe_dir(dir_name).map(|p| p.to_string_lossy().to_string()))
+        .unwrap_or_else(|| dir_name.to_string())
+}
+
+/// Builds sessions from time-sorted records, applying the sub-agent policy.
+pub fn build_sessions(records: &[TimestampRecord], rules: &SessionRules, debug: bool) -> Vec<Session> {
+    let (agent, main): (Vec<&TimestampRecord>, Vec<&TimestampRecord>) = records.iter().partition(|r| r.subagent);
+    
+    let mut sessions = match rules.subagents {
+        SubagentPolicy::Merge => build_sessions_from_records(records, rules, debug),
+        _ => build_sessions_from_records(&main, rules, debug),
+    };
+    link_subagents(&mut sessions, &main, &agent);
     
-    let file_path = tool_input.file_path.as_ref()
-        .or(tool_input.path.as_ref())
-        .or(tool_input.workdir.as_ref())?;
+    if rules.subagents == SubagentPolicy::Separate {
+        let separate = agent_sessions(&agent, &sessions, rules, debug);
+        if debug {
+            eprintln!("[DEBUG] {} agent-driven sessions outside the main ones", separate.len());
+        }
+        sessions.extend(separate);
+        sessions.sort_by_key(|s| s.start_time);
+    }
     
-    if !file_path.contains("/Programowanie/") {
-        return None;
+    sessions
+}
+
+/// Counts each sub-agent record in a session holding records of its parent
+/// log session: the last such session starting before it, else the first.
+/// Records whose parent left no session are not counted anywhere.
+fn link_subagents(sessions: &mut [Session], main: &[&TimestampRecord], agent: &[&TimestampRecord]) {
+    let mut by_parent: HashMap<&str, Vec<usize>> = HashMap::new();
+    for record in main {
+        let Some(id) = record.session.as_deref() else { continue };
+        let after = sessions.partition_point(|s| s.start_time <= record.timestamp);
+        let Some(index) = after.checked_sub(1).filter(|i| sessions[*i].end_time >= record.timestamp) else { continue };
+        let indices = by_parent.entry(id).or_default();
+        if indices.last() != Some(&index) {
+            indices.push(index);
+        }
     }
     
-    let parts: Vec<&str> = file_path.split("/Programowanie/").collect();
-    if parts.len() < 2 {
-        return None;
+    for record in agent {
+        let Some(indices) = record.session.as_deref().and_then(|id| by_parent.get(id)) else { continue };
+        let after = indices.partition_point(|i| sessions[*i].start_time <= record.timestamp);
+        sessions[indices[after.saturating_sub(1)]].subagent_events += 1;
     }
+}
+
+/// Sessions of sub-agent activity with the time already covered by `main`
+/// sessions cut out, so nothing is counted twice. No padding: nobody reads
+/// an agent's intermediate output.
+fn agent_sessions(agent: &[&TimestampRecord], main: &[Session], rules: &SessionRules, debug: bool) -> Vec<Session> {
+    let agent_rules = SessionRules {
+        padding: Padding { before_seconds: 0, after_seconds: 0, min_burst_seconds: 0 },
+        ..rules.clone()
+    };
     
-    let after_prog = parts[1];
-    let project_name = after_prog.split('/').next()?;
+    let mut busy: Vec<_> = main.iter().flat_map(|s| s.segments.iter().copied()).collect();
+    busy.sort();
     
-    if project_name.is_empty() {
-        return None;
+    build_sessions_from_records(agent, &agent_rules, debug)
+        .into_iter()
+        .filter_map(|mut session| {
+            let from = agent.partition_point(|r| r.timestamp < session.start_time);
+            let to = agent.partition_point(|r| r.timestamp <= session.end_time);
+            session.segments = subtract_intervals(&session.segments, &busy);
+            let segments = &session.segments;
+            session.subagent_events = agent[from..to]
+                .iter()
+                .filter(|r| {
+                    let after = segments.partition_point(|(start, _)| *start <= r.timestamp);
+                    after > 0 && r.timestamp <= segments[after - 1].1
+                })
+                .count();
+            session.start_time = session.segments.first()?.0;
+            session.end_time = session.segments.last()?.1;
+            session.duration_seconds = session.segments.iter().map(|(s, e)| (*e - *s).num_seconds()).sum();
+            session.estimated_seconds = 0;
+            session.agent_driven = true;
+            session.id = session.id.replace("global-", "agent-");
+            (session.duration_seconds > 0).then_some(session)
+        })
+        .collect()
+}
+
+/// `intervals` minus the sorted list `cut`, dropping empty pieces.
+fn subtract_intervals(
+    intervals: &[(NaiveDateTime, NaiveDateTime)],
+    cut: &[(NaiveDateTime, NaiveDateTime)],
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let mut result = Vec::new();
+    
+    for &(start, end) in intervals {
+        let mut cursor = start;
+        for &(cut_start, cut_end) in cut {
+            if cut_end <= cursor || cut_start >= end {
+                continue;
+            }
+            if cut_start > cursor {
+                result.push((cursor, cut_start));
+            }
+            cursor = cursor.max(cut_end);
+        }
+        if cursor < end {
+            result.push((cursor, end));
+        }
     }
     
-    let normalized = project_name.replace('_', "-");
-    Some(format!("-home-jarx-Programowanie-{}", normalized))
+    result
+}
+
+/// What the records of one session add up to.
+#[derive(Default)]
+struct Tally {
+    projects: HashMap<String, usize>,
+    marks: Vec<(NaiveDateTime, String)>,
+    paths: HashMap<String, usize>,
+    stats: SessionStats,
+}
+
+impl Tally {
+    fn count(&mut self, record: &TimestampRecord) {
+        self.stats.add(record);
+        *self.projects.entry(record.project.clone()).or_insert(0) += 1;
+        if self.marks.last().is_none_or(|(_, project)| *project != record.project) {
+            self.marks.push((record.timestamp, record.project.clone()));
+        }
+        if let Some(path) = &record.path {
+            *self.paths.entry(path.clone()).or_insert(0) += 1;
+        }
+    }
 }
 
-fn build_sessions_from_records(records: &[TimestampRecord], debug: bool) -> Vec<Session> {
+/// Cuts time-sorted records into sessions. A gap ends the session when it is
+/// longer than the gap threshold of the project worked on before it; the
+/// minimum length is checked against the session's dominant project, after
+/// padding. Padding never reaches into the previous or the next session, and
+/// a burst is never extended into the next one.
+///
+/// Inside a session only part of a pause may be credited (see
+/// `ActivityWeights`); the rest splits the session into active segments.
+fn build_sessions_from_records<R: Borrow<TimestampRecord>>(records: &[R], rules: &SessionRules, debug: bool) -> Vec<Session> {
     let mut sessions = Vec::new();
+    let records: Vec<&TimestampRecord> = records.iter().map(Borrow::borrow).collect();
     
     if records.is_empty() {
         return sessions;
     }
     
-    let mut session_start = records[0].timestamp;
-    let mut session_end = records[0].timestamp;
-    let mut session_projects: HashMap<String, usize> = HashMap::new();
-    session_projects.insert(records[0].project.clone(), 1);
-    let mut session_count = 0;
+    let mut segments = vec![(records[0].timestamp, records[0].timestamp)];
+    let mut last = records[0];
+    let mut tally = Tally::default();
+    tally.count(records[0]);
     
-    for i in 1..records.len() {
-        let gap = (records[i].timestamp - session_end).num_seconds();
-        
-        if gap > SESSION_GAP_SECONDS {
-            let duration = (session_end - session_start).num_seconds();
-            if duration >= MIN_SESSION_SECONDS {
-                let dominant_project = session_projects
-                    .iter()
-                    .max_by_key(|(_, count)| *count)
-                    .map(|(proj, _)| proj.clone())
-                    .unwrap_or_else(|| "unknown".to_string());
-                
-                sessions.push(Session {
-                    id: format!("global-{}", session_count),
-                    project: dominant_project,
-                    project_counts: session_projects.clone(),
-                    start_time: session_start,
-                    end_time: session_end,
-                    duration_seconds: duration,
-                });
-                session_count += 1;
-            }
-            session_start = records[i].timestamp;
-            session_projects.clear();
-        }
-        session_end = records[i].timestamp;
-        *session_projects.entry(records[i].project.clone()).or_insert(0) += 1;
-    }
+    let mut previous_end: Option<NaiveDateTime> = None;
     
-    let duration = (session_end - session_start).num_seconds();
-    if duration >= MIN_SESSION_SECONDS {
-        let dominant_project = session_projects
+    let mut close_session = |mut segments: Vec<(NaiveDateTime, NaiveDateTime)>, next: Option<NaiveDateTime>, tally: Tally| {
+        let dominant_project = tally
+            .projects
             .iter()
             .max_by_key(|(_, count)| *count)
             .map(|(proj, _)| proj.clone())
             .unwrap_or_else(|| "unknown".to_string());
+        let thresholds = rules.for_project(&dominant_project);
+        
+        let duration: i64 = segments.iter().map(|(s, e)| (*e - *s).num_seconds()).sum();
+        let Some(first) = segments.first_mut() else { return };
         
-        sessions.push(Session {
-            id: format!("global-{}", session_count),
-            project: dominant_project,
-            project_counts: session_projects.clone(),
-            start_time: session_start,
-            end_time: session_end,
-            duration_seconds: duration,
-        });
+        let mut estimated = rules.padding.before_seconds;
+        if let Some(previous) = previous_end {
+            estimated = estimated.min((first.0 - previous).num_seconds().max(0));
+        }
+        first.0 -= chrono::Duration::seconds(estimated);
+        let start = first.0;
+        
+        let count = segments.len();
+        for i in 0..count {
+            let (segment_start, segment_end) = segments[i];
+            let mut extra = (rules.padding.min_burst_seconds - (segment_end - segment_start).num_seconds()).max(0);
+            if i + 1 == count {
+                extra = extra.max(rules.padding.after_seconds);
+            }
+            if let Some(limit) = segments.get(i + 1).map(|s| s.0).or(next) {
+                extra = extra.min((limit - segment_end).num_seconds());
+            }
+            segments[i].1 += chrono::Duration::seconds(extra);
+            estimated += extra;
+        }
+        let end = segments[count - 1].1;
+        previous_end = Some(end);
+        
+        if duration + estimated >= thresholds.min_session_seconds {
+            sessions.push(Session {
+                id: format!("global-{}", sessions.len()),
+                project: dominant_project,
+                project_counts: tally.projects,
+                project_marks: tally.marks,
+                path_counts: tally.paths,
+                start_time: start,
+                segments,
+                end_time: end,
+                duration_seconds: duration,
+                estimated_seconds: estimated,
+                thresholds,
+                subagent_events: 0,
+                agent_driven: false,
+                stats: tally.stats,
+            });
+        }
+    };
+    
+    // Thresholds by raw project key; `for_project` allocates a display name.
+    let mut thresholds: HashMap<&str, Thresholds> = HashMap::new();
+    
+    for &record in &records[1..] {
+        let gap = (record.timestamp - last.timestamp).num_seconds();
+        let gap_limit = thresholds
+            .entry(last.project.as_str())
+            .or_insert_with(|| rules.for_project(&last.project))
+            .gap_seconds;
+        
+        if gap > gap_limit {
+            close_session(std::mem::take(&mut segments), Some(record.timestamp), std::mem::take(&mut tally));
+            segments.push((record.timestamp, record.timestamp));
+        } else {
+            let credited = rules.activity.credit(last, gap);
+            let current = segments.last_mut().expect("session has a segment");
+            if credited >= gap {
+                current.1 = record.timestamp;
+            } else {
+                current.1 = last.timestamp + chrono::Duration::seconds(credited);
+                segments.push((record.timestamp, record.timestamp));
+            }
+        }
+        last = record;
+        tally.count(record);
     }
     
+    close_session(segments, None, tally);
+    
     if debug && !sessions.is_empty() {
         let total_duration: i64 = sessions.iter().map(|s| s.duration_seconds).sum();
-        eprintln!("[DEBUG] Total session time: {}s ({:.2}h)", 
-            total_duration, total_duration as f64 / 3600.0);
+        let total_estimated: i64 = sessions.iter().map(|s| s.estimated_seconds).sum();
+        eprintln!("[DEBUG] Total session time: {}s ({:.2}h) + estimated {}s", 
+            total_duration, total_duration as f64 / 3600.0, total_estimated);
     }
     
     sessions
 }
 
-fn parse_timestamp(ts: &str) -> Option<NaiveDateTime> {
+pub fn parse_timestamp(ts: &str) -> Option<NaiveDateTime> {
     let cleaned = ts.trim_end_matches('Z').replace('T', " ");
     let without_ms = cleaned.split('.').next()?;
     NaiveDateTime::parse_from_str(without_ms, "%Y-%m-%d %H:%M:%S").ok()
 }
 
+/// `projects/<project>/<session>/subagents/agent-*.jsonl`
+fn is_subagent_log(path: &Path) -> bool {
+    path.components().any(|c| c.as_os_str() == "subagents")
+}
+
 fn extract_project_name(path: &Path) -> String {
     let path_str = path.to_string_lossy();
     
@@ -489,7 +1104,14 @@ fn extract_project_name(path: &Path) -> String {
         return "transcripts".to_string();
     }
     
-    if let Some(parent) = path.parent() {
+    // Sub-agent logs sit two levels below the parent session's project dir.
+    let project_dir = if is_subagent_log(path) {
+        path.parent().and_then(Path::parent)
+    } else {
+        Some(path)
+    };
+    
+    if let Some(parent) = project_dir.and_then(Path::parent) {
         let parent_name = parent.file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_default();
@@ -505,6 +1127,8 @@ fn extract_project_name(path: &Path) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::TempDir;
+    use chrono::Timelike;
     
     #[test]
     fn test_parse_timestamp() {
@@ -519,4 +1143,304 @@ mod tests {
         let name = extract_project_name(path);
         assert_eq!(name, "-home-jarx-Programowanie-farmaster2");
     }
+    
+    fn records(project: &str, minutes: &[i64]) -> Vec<TimestampRecord> {
+        let base = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap().and_hms_opt(18, 0, 0).unwrap();
+        minutes
+            .iter()
+            .map(|m| TimestampRecord::new(base + chrono::Duration::minutes(*m), project.to_string(), EventKind::Other))
+            .collect()
+    }
+    
+    #[test]
+    fn test_session_thresholds_per_project() {
+        let config: Config = serde_json::from_str(r#"{
+            "sessions": {
+                "gap_minutes": 30,
+                "min_session_minutes": 5,
+                "projects": {"farmaster2": {"gap_minutes": 60, "min_session_minutes": 1}}
+            }
+        }"#).unwrap();
+        let rules = SessionRules::from_config(&config, None, None).unwrap();
+        
+        // A 45-minute pause splits a session, except in a project allowed 60.
+        let other = build_sessions_from_records(&records("-home-jarx-Programowanie-other", &[0, 10, 55, 65]), &rules, false);
+        assert_eq!(other.len(), 2);
+        let farm = build_sessions_from_records(&records("-home-jarx-Programowanie-farmaster2", &[0, 10, 55, 65]), &rules, false);
+        assert_eq!(farm.len(), 1);
+        assert_eq!(farm[0].thresholds.gap_seconds, 3600);
+        
+        // A 2-minute fix counts only where the minimum was lowered.
+        assert!(build_sessions_from_records(&records("-home-jarx-Programowanie-other", &[0, 2]), &rules, false).is_empty());
+        assert_eq!(build_sessions_from_records(&records("-home-jarx-Programowanie-farmaster2", &[0, 2]), &rules, false).len(), 1);
+        
+        // CLI values win over config, overrides included.
+        let cli_rules = SessionRules::from_config(&config, Some(20), None).unwrap();
+        assert_eq!(cli_rules.for_project("-home-jarx-Programowanie-farmaster2").gap_seconds, 20 * 60);
+    }
+    
+    #[test]
+    fn test_session_padding() {
+        let config: Config = serde_json::from_str(r#"{
+            "sessions": {"min_session_minutes": 5, "padding_after_minutes": 10, "min_burst_minutes": 15}
+        }"#).unwrap();
+        let rules = SessionRules::from_config(&config, None, None).unwrap();
+        
+        // A lone prompt becomes a 15-minute burst instead of being dropped.
+        let lone = build_sessions_from_records(&records("p", &[0]), &rules, false);
+        assert_eq!(lone.len(), 1);
+        assert_eq!((lone[0].duration_seconds, lone[0].estimated_seconds), (0, 15 * 60));
+        
+        // A longer session gets the reading time after its last event.
+        let long = build_sessions_from_records(&records("p", &[0, 20]), &rules, false);
+        assert_eq!(long[0].estimated_seconds, 10 * 60);
+        assert_eq!(long[0].end_time - long[0].start_time, chrono::Duration::minutes(30));
+        
+        // Padding stops where the next session starts.
+        let capped_rules = SessionRules::from_config(&config, Some(5), None).unwrap();
+        let capped = build_sessions_from_records(&records("p", &[0, 3, 10]), &capped_rules, false);
+        assert_eq!(capped[0].estimated_seconds, 7 * 60);
+    }
+    
+    #[test]
+    fn test_padding_per_burst() {
+        let config: Config = serde_json::from_str(r#"{
+            "sessions": {"min_session_minutes": 0, "padding_before_minutes": 40, "min_burst_minutes": 3,
+                         "activity": {"assistant_weight": 0.0}}
+        }"#).unwrap();
+        let rules = SessionRules::from_config(&config, Some(30), None).unwrap();
+        let mut records = records("p", &[0, 10, 20, 52]);
+        for record in &mut records {
+            record.kind = EventKind::Assistant;
+        }
+        let minute = |m| records[0].timestamp + chrono::Duration::minutes(m);
+        
+        // Uncredited pauses leave three bursts; each is brought up to three minutes.
+        let sessions = build_sessions_from_records(&records, &rules, false);
+        assert_eq!(sessions[0].segments, [(minute(-40), minute(0)), (minute(10), minute(13)), (minute(20), minute(23))]);
+        assert_eq!(sessions[0].estimated_seconds, (40 + 3 + 3) * 60);
+        
+        // Thinking time before a session stops at the end of the previous one.
+        assert_eq!(sessions[1].segments, [(minute(23), minute(52))]);
+        assert_eq!(sessions[1].estimated_seconds, 29 * 60);
+    }
+    
+    #[test]
+    fn test_event_kind() {
+        let kind = |line: &str| serde_json::from_str::<JsonlEntry>(line).unwrap().event_kind();
+        assert_eq!(kind(r#"{"type":"user","message":{"role":"user","content":"popraw testy"}}"#), EventKind::Prompt);
+        assert_eq!(kind(r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"ok"}]}}"#), EventKind::Tool);
+        assert_eq!(kind(r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Bash"}]}}"#), EventKind::Tool);
+        assert_eq!(kind(r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Gotowe"}]}}"#), EventKind::Assistant);
+        assert_eq!(kind(r#"{"type":"summary"}"#), EventKind::Other);
+    }
+    
+    #[test]
+    fn test_session_stats() {
+        let dir = TempDir::new("stats");
+        let file = dir.write("-home-ola-code-app/s1.jsonl", [
+            r#"{"type":"user","timestamp":"2026-01-20T18:00:00Z","gitBranch":"main","message":{"role":"user","content":"napraw build"},"unknownField":{"a":1}}"#,
+            r#"{"type":"assistant","timestamp":"2026-01-20T18:01:00Z","gitBranch":"main","message":{"role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"tool_use","name":"Bash","input":{}},{"type":"tool_use","name":"Read","input":{"file_path":"/home/ola/code/app/Cargo.toml"}}]}}"#,
+            r#"{"type":"assistant","timestamp":"2026-01-20T18:02:00Z","gitBranch":"fix/ci","message":{"model":"<synthetic>","content":[{"type":"tool_use","name":"Bash","input":{}}]}}"#,
+            r#"{"timestamp":"2026-01-20T18:09:00Z","message":{"role":"user","content":"dzięki"}}"#,
+        ].join("\n"));
+        
+        let collected = collect_timestamps_from_file(&file, &file, &ProjectResolver::default(), &mut SeenRecords::default());
+        let sessions = build_sessions(&collected, &SessionRules::default(), false);
+        let stats = &sessions[0].stats;
+        
+        assert_eq!((stats.prompts, stats.tool_calls), (2, 3));
+        assert_eq!(stats.tools.get("Bash"), Some(&2));
+        assert_eq!(stats.models.iter().collect::<Vec<_>>(), ["claude-sonnet-4-5"]);
+        assert_eq!(stats.branches.iter().collect::<Vec<_>>(), ["fix/ci", "main"]);
+    }
+    
+    #[test]
+    fn test_idle_after_assistant_is_capped() {
+        let config: Config = serde_json::from_str(r#"{
+            "sessions": {"activity": {"prompt_weight": 1.0, "assistant_weight": 0.5, "assistant_cap_minutes": 5}}
+        }"#).unwrap();
+        let rules = SessionRules::from_config(&config, None, None).unwrap();
+        
+        let base = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap().and_hms_opt(18, 0, 0).unwrap();
+        let at = |m: i64, kind| TimestampRecord::new(base + chrono::Duration::minutes(m), "p".to_string(), kind);
+        // prompt, answer after 4 min, then 20 min of silence before the next prompt.
+        let records = vec![
+            at(0, EventKind::Prompt),
+            at(4, EventKind::Assistant),
+            at(24, EventKind::Prompt),
+            at(30, EventKind::Assistant),
+        ];
+        
+        let sessions = build_sessions_from_records(&records, &rules, false);
+        assert_eq!(sessions.len(), 1);
+        // 4 + min(10, 5) + 6 minutes active, in two segments.
+        assert_eq!(sessions[0].duration_seconds, 15 * 60);
+        assert_eq!(sessions[0].segments.len(), 2);
+        assert_eq!(sessions[0].end_time - sessions[0].start_time, chrono::Duration::minutes(30));
+    }
+    
+    #[test]
+    fn test_subagent_log_project() {
+        let path = Path::new("/home/jarx/.claude/projects/-home-jarx-Programowanie-farmaster2/5f1c/subagents/agent-a1.jsonl");
+        assert!(is_subagent_log(path));
+        assert_eq!(extract_project_name(path), "-home-jarx-Programowanie-farmaster2");
+    }
+    
+    #[test]
+    fn test_project_keyed_by_cwd() {
+        let dir = TempDir::new("cwd");
+        let file = dir.write("-home-ola-code-after15-core/s1.jsonl", concat!(
+            r#"{"timestamp":"2026-01-20T18:00:00Z","cwd":"/home/ola/code/after15-core"}"#, "\n",
+            r#"{"timestamp":"2026-01-20T18:05:00Z","cwd":"/home/ola/code/after15-core/src"}"#, "\n",
+        ));
+        
+        let collected = collect_timestamps_from_file(&file, &file, &ProjectResolver::default(), &mut SeenRecords::default());
+        assert_eq!(collected.len(), 2);
+        assert!(collected.iter().all(|r| r.project == "/home/ola/code/after15-core"));
+    }
+    
+    #[test]
+    fn test_synced_roots_deduplicated() {
+        // The same session under this machine's logs and a copy synced from
+        // a second laptop that also has one newer record.
+        let base = TempDir::new("roots");
+        let line = |minute| format!(r#"{{"timestamp":"2026-01-20T18:{:02}:00Z","sessionId":"s1"}}"#, minute);
+        base.write("local/-srv-app/s1.jsonl", [line(0), line(5), line(5)].join("\n"));
+        base.write("laptop2/-srv-app/s1.jsonl", [line(0), line(5), line(10)].join("\n"));
+        base.write("laptop2/-srv-app/s1/subagents/agent-1.jsonl", line(7));
+
+        let config: LogSourceConfig = serde_json::from_value(serde_json::json!({
+            "dirs": [base.join("local"), base.join("laptop2")],
+            "exclude": ["**/subagents/**"],
+        }))
+        .unwrap();
+        let source = ClaudeSource::from_config(&config);
+        let mut minutes: Vec<u32> = source
+            .records(Scope::All, &ProjectResolver::default(), false)
+            .iter()
+            .map(|r| r.timestamp.minute())
+            .collect();
+        minutes.sort();
+
+        // A repeat within one file stays; the synced copy only adds 18:10.
+        assert_eq!(minutes, [0, 5, 5, 10]);
+
+    }
+
+    #[test]
+    fn test_subagent_records_in_same_second_kept() {
+        // A sub-agent log carries its parent's sessionId; records of both
+        // within one second are distinct, by uuid or by sub-second time.
+        let base = TempDir::new("same-second");
+        base.write("-srv-app/s1/subagents/agent-1.jsonl", [
+            r#"{"uuid":"b","timestamp":"2026-01-20T18:00:00.500Z","sessionId":"s1"}"#,
+            r#"{"timestamp":"2026-01-20T18:00:00.900Z","sessionId":"s1"}"#,
+        ].join("\n"));
+        base.write("-srv-app/s1.jsonl", [
+            r#"{"uuid":"a","timestamp":"2026-01-20T18:00:00.100Z","sessionId":"s1"}"#,
+            r#"{"timestamp":"2026-01-20T18:00:00.300Z","sessionId":"s1"}"#,
+        ].join("\n"));
+
+        let config: LogSourceConfig = serde_json::from_value(serde_json::json!({"dirs": [base.path()]})).unwrap();
+        let source = ClaudeSource::from_config(&config);
+        let records = source.records(Scope::All, &ProjectResolver::default(), false);
+
+        assert_eq!(records.iter().filter(|r| !r.subagent).count(), 2);
+        assert_eq!(records.iter().filter(|r| r.subagent).count(), 2);
+    }
+
+    #[test]
+    fn test_subagents_linked_when_ignored() {
+        let base = TempDir::new("ignored-agents");
+        let line = |minute| format!(r#"{{"timestamp":"2026-01-20T18:{:02}:00Z","sessionId":"s1"}}"#, minute);
+        base.write("-srv-app/s1.jsonl", [line(0), line(10), line(20)].join("\n"));
+        base.write("-srv-app/s1/subagents/agent-1.jsonl", [line(5), line(15)].join("\n"));
+
+        let config: LogSourceConfig = serde_json::from_value(serde_json::json!({"dirs": [base.path()]})).unwrap();
+        let mut records = ClaudeSource::from_config(&config).records(Scope::All, &ProjectResolver::default(), false);
+        records.sort_by_key(|r| r.timestamp);
+        let rules = SessionRules { subagents: SubagentPolicy::Ignore, ..SessionRules::default() };
+        let sessions = build_sessions(&records, &rules, false);
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].subagent_events, 2);
+    }
+
+    #[test]
+    fn test_log_file_patterns() {
+        let config: LogSourceConfig = serde_json::from_str(
+            r#"{"include": ["/logs/**/work-*/**", "*.jsonl"], "exclude": ["agent-*.jsonl"]}"#,
+        )
+        .unwrap();
+        let files = LogFiles::from_config(&config, &[]);
+
+        assert!(files.is_wanted(Path::new("/logs/a/work-x/s1.jsonl")));
+        assert!(files.is_wanted(Path::new("/elsewhere/s1.jsonl")));
+        assert!(!files.is_wanted(Path::new("/logs/a/work-x/agent-1.jsonl")));
+        assert!(!files.is_wanted(Path::new("/logs/a/home/notes.txt")));
+    }
+
+    #[test]
+    fn test_subagent_policies() {
+        let mut records: Vec<_> = records("p", &[0, 20])
+            .into_iter()
+            .map(|r| TimestampRecord { session: Some("s1".to_string()), ..r })
+            .collect();
+        records.extend(records_for_agent("s1", &[15, 40, 60, 80]));
+        // Inside the main session's span, but spawned by another log session.
+        records.extend(records_for_agent("s2", &[10]));
+        records.sort_by_key(|r| r.timestamp);
+        
+        let policy = |name: &str| {
+            let config: Config = serde_json::from_str(&format!(r#"{{"sessions": {{"subagents": "{}"}}}}"#, name)).unwrap();
+            SessionRules::from_config(&config, None, None).unwrap()
+        };
+        
+        let ignored = build_sessions(&records, &policy("ignore"), false);
+        assert_eq!(ignored.len(), 1);
+        // All of s1's agent events belong to its session; s2's to none.
+        assert_eq!((ignored[0].duration_seconds, ignored[0].subagent_events), (20 * 60, 4));
+        
+        let merged = build_sessions(&records, &policy("merge"), false);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].duration_seconds, 80 * 60);
+        
+        // The agent's 15-80 run minus the main 0-20 session leaves 60 minutes.
+        let separate = build_sessions(&records, &policy("separate"), false);
+        assert_eq!(separate.len(), 2);
+        assert!(separate[1].agent_driven);
+        assert_eq!(separate[1].segments[0].0 - separate[0].start_time, chrono::Duration::minutes(20));
+        assert_eq!(separate[1].duration_seconds, 60 * 60);
+        // The agent session keeps only the events outside the main one.
+        assert_eq!((separate[0].subagent_events, separate[1].subagent_events), (4, 3));
+    }
+    
+    fn records_for_agent(parent: &str, minutes: &[i64]) -> Vec<TimestampRecord> {
+        records("p", minutes)
+            .into_iter()
+            .map(|r| TimestampRecord { subagent: true, session: Some(parent.to_string()), ..r })
+            .collect()
+    }
+    
+    #[test]
+    fn test_time_weighted_attribution() {
+        let mixed = [records("a", &[0, 1, 2, 3, 4, 5]), records("b", &[25, 45])].concat();
+        
+        let sessions = build_sessions_from_records(&mixed, &SessionRules::default(), false);
+        assert_eq!(sessions.len(), 1);
+        
+        // The pause after a's last record is still a's: 25 min of a, 20 of b.
+        let whole = &sessions[0].segments;
+        let by_time = sessions[0].project_weights(Attribution::Time, whole);
+        assert_eq!((by_time["a"], by_time["b"]), (25.0 * 60.0, 20.0 * 60.0));
+        
+        // Overtime from 18:30 on is b's alone.
+        let late = [(mixed[6].timestamp + chrono::Duration::minutes(5), mixed[7].timestamp)];
+        let in_overtime = sessions[0].project_weights(Attribution::Time, &late);
+        assert_eq!(in_overtime, HashMap::from([("b".to_string(), 15.0 * 60.0)]));
+        
+        let by_records = sessions[0].project_weights(Attribution::Records, whole);
+        assert_eq!((by_records["a"], by_records["b"]), (6.0, 2.0));
+    }
 }
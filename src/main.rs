@@ -1,14 +1,29 @@
 mod config;
 mod schedule;
+mod holidays;
+mod overrides;
 mod overtime;
 mod jsonl;
 mod report;
 mod archive;
 mod pdf;
+mod ics;
+mod rota;
+mod projects;
+mod timezone;
+mod export;
+mod git;
+mod breakdown;
+mod sources;
+mod shell;
+mod assistants;
+#[cfg(test)]
+mod testutil;
 
-use clap::Parser;
-use chrono::{Local, Datelike};
+use clap::{Parser, Subcommand, ValueEnum};
+use chrono::{Datelike, Utc};
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use report::format_hm;
 
@@ -30,16 +45,123 @@ struct Cli {
     
     #[arg(long, help = "Debug output")]
     debug: bool,
+    
+    #[arg(long, value_name = "PROJECT", help = "Break a project's overtime down by directory (with --month)")]
+    by_path: Option<String>,
+    
+    #[arg(long, default_value_t = 1, requires = "by_path", help = "Directory levels for --by-path")]
+    depth: usize,
+    
+    #[arg(long, conflicts_with = "by_path", help = "Break overtime down by tool (with --month)")]
+    by_tool: bool,
+    
+    #[arg(long, global = true, help = "Pause (minutes) that ends a session; overrides config")]
+    gap_minutes: Option<u32>,
+    
+    #[arg(long, global = true, help = "Minimum session length (minutes); overrides config")]
+    min_session_minutes: Option<u32>,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Manage the shift schedule")]
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    #[command(about = "Export computed overtime")]
+    Export {
+        #[arg(long, value_enum, default_value = "ics", help = "Output format")]
+        format: ExportFormat,
+        
+        #[arg(long, help = "Month to export (YYYY-MM), defaults to the current one")]
+        month: Option<String>,
+        
+        #[arg(long, help = "Output file (default: ~/nadgodziny_YYYY-MM.ics)")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Ics,
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction {
+    #[command(about = "Import shifts from an iCalendar (.ics) rota")]
+    Import {
+        #[arg(help = "Path to the .ics file")]
+        file: PathBuf,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
     let config = config::load_config();
+    let schedule = match schedule::Schedule::from_config(&config.schedule)
+        .and_then(|s| Ok(s.with_time_zones(timezone::TimeZones::from_config(&config)?)))
+    {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            eprintln!("[BŁĄD] {}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    // Importing only needs the shift types, so neither overrides nor a
+    // previously imported rota can stand in its way.
+    if let Some(Command::Schedule { action: ScheduleAction::Import { file } }) = &cli.command {
+        import_schedule(file, &config, &schedule);
+        return;
+    }
+    
+    let schedule = match overrides::load_overrides().and_then(|entries| schedule.with_overrides(&entries)) {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            eprintln!("[BŁĄD] {}", e);
+            std::process::exit(1);
+        }
+    };
+    let schedule = match rota::load_imported_schedule().and_then(|imported| schedule.clone().with_imported(&imported)) {
+        Ok(with_imported) => with_imported,
+        Err(e) => {
+            eprintln!("[UWAGA] {} - pomijam zaimportowany grafik", e);
+            schedule
+        }
+    };
+    let rules = match jsonl::SessionRules::from_config(&config, cli.gap_minutes, cli.min_session_minutes) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("[BŁĄD] {}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    let sources = sources::Sources::from_config(&config, schedule.zones(), cli.gap_minutes.unwrap_or(config.sessions.gap_minutes));
+    
+    match &cli.command {
+        Some(Command::Export { format: ExportFormat::Ics, month, output }) => {
+            let month = month.clone().unwrap_or_else(|| schedule.today().format("%Y-%m").to_string());
+            match export::export_ics(&sources, &schedule, &rules, &month, output.clone(), cli.debug) {
+                Ok((path, count)) => println!("Wyeksportowano {} bloków nadgodzin: {}", count, path.display()),
+                Err(e) => {
+                    eprintln!("[BŁĄD] {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Schedule { .. }) | None => {}
+    }
     
     if let Some(explain_date_str) = &cli.explain {
         match chrono::NaiveDate::parse_from_str(explain_date_str, "%Y-%m-%d") {
             Ok(explain_date) => {
-                print_explain(explain_date, cli.debug);
+                print_explain(explain_date, &config, &sources, &schedule, &rules, cli.debug);
                 return;
             }
             Err(_) => {
@@ -49,12 +171,32 @@ fn main() {
         }
     }
     
+    if cli.by_path.is_some() || cli.by_tool {
+        let month = cli.month.clone().unwrap_or_else(|| schedule.today().format("%Y-%m").to_string());
+        let (from, to) = match export::parse_month(&month) {
+            Ok(range) => range,
+            Err(e) => {
+                eprintln!("[BŁĄD] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let sessions = jsonl::load_sessions_for_range(from, to, &sources, &schedule, &rules, cli.debug);
+        if let Some(project) = &cli.by_path {
+            let rows = breakdown::path_breakdown(&sessions, &schedule, &rules, project, cli.depth.max(1), from, to);
+            report::print_path_breakdown(project, cli.depth.max(1), &month, &rows, &config);
+        } else {
+            let rows = breakdown::tool_breakdown(&sessions, &schedule, from, to);
+            report::print_tool_breakdown(&month, &rows, &config);
+        }
+        return;
+    }
+    
     let summary = jsonl::load_daily_summary_full(cli.debug);
     let mut daily_hours = summary.hours;
     let mut daily_projects = summary.projects;
     
-    let today = Local::now().date_naive();
-    let recent_data = jsonl::load_recent_overtime(7, cli.debug);
+    let today = schedule.today();
+    let recent_data = jsonl::load_recent_overtime(7, &sources, &schedule, &rules, cli.debug);
     
     for (date, hours) in recent_data.hours {
         if date == today || !daily_hours.contains_key(&date) {
@@ -67,10 +209,10 @@ fn main() {
         }
     }
     
-    archive::archive_overtime(&daily_hours, &daily_projects, cli.debug);
+    archive::archive_overtime(&daily_hours, &daily_projects, &schedule, cli.debug);
     
     if cli.pdf {
-        match pdf::generate_pdf(&daily_projects, &config, cli.month.as_deref()) {
+        match pdf::generate_pdf(&daily_projects, &config, &rules.projects, schedule.zones(), cli.month.as_deref()) {
             Ok(path) => println!("PDF wygenerowany: {}", path.display()),
             Err(e) => {
                 eprintln!("[BLAD] {}", e);
@@ -78,14 +220,31 @@ fn main() {
             }
         }
     } else if cli.statusline {
-        print_statusline(&daily_hours);
+        print_statusline(&daily_hours, &schedule);
     } else {
-        report::print_full_report(&daily_hours, &daily_projects, &config, cli.month.as_deref());
+        report::print_full_report(&daily_hours, &daily_projects, &config, &schedule, &rules.projects, cli.month.as_deref());
+    }
+}
+
+fn import_schedule(file: &std::path::Path, config: &config::Config, schedule: &schedule::Schedule) {
+    match rota::import_ics(file, &config.schedule.ics_mapping, schedule) {
+        Ok(summary) => {
+            println!("Zaimportowano {} dni z {} wydarzeń → {}", 
+                summary.days, summary.events, summary.path.display());
+            if !summary.unmapped.is_empty() {
+                let names: Vec<_> = summary.unmapped.into_iter().collect();
+                println!("Pominięto wydarzenia bez mapowania (schedule.ics_mapping): {}", names.join(", "));
+            }
+        }
+        Err(e) => {
+            eprintln!("[BŁĄD] {}", e);
+            std::process::exit(1);
+        }
     }
 }
 
-fn print_statusline(daily: &HashMap<chrono::NaiveDate, f64>) {
-    let today = Local::now().date_naive();
+fn print_statusline(daily: &HashMap<chrono::NaiveDate, f64>, schedule: &schedule::Schedule) {
+    let today = schedule.today();
     let today_hours = daily.get(&today).copied().unwrap_or(0.0);
     
     let month_hours: f64 = daily.iter()
@@ -93,40 +252,65 @@ fn print_statusline(daily: &HashMap<chrono::NaiveDate, f64>) {
         .map(|(_, h)| h)
         .sum();
     
-    let icon = if schedule::is_overtime_hour(Local::now()) { "🌙" } else { "🏢" };
+    let icon = if schedule.is_overtime_hour(Utc::now().naive_utc()) { "🌙" } else { "🏢" };
     
     println!("{} {}/{}", icon, format_hm(today_hours), format_hm(month_hours));
 }
 
-fn print_explain(date: chrono::NaiveDate, debug: bool) {
-    use chrono_tz::Europe::Warsaw;
+fn describe_thresholds(thresholds: &jsonl::Thresholds) -> String {
+    format!("przerwa > {} min kończy sesję, minimum {} min",
+        thresholds.gap_seconds / 60, thresholds.min_session_seconds / 60)
+}
+
+fn describe_activity(stats: &jsonl::SessionStats) -> Option<String> {
+    if stats.prompts == 0 && stats.tool_calls == 0 {
+        return None;
+    }
+    let mut tools: Vec<_> = stats.tools.iter().collect();
+    tools.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let top: Vec<String> = tools.iter().take(5).map(|(name, count)| format!("{} {}", name, count)).collect();
+    let tools_desc = if top.is_empty() { String::new() } else { format!(" ({})", top.join(", ")) };
+    Some(format!("prompty: {}, wywołania narzędzi: {}{}", stats.prompts, stats.tool_calls, tools_desc))
+}
+
+fn print_explain(date: chrono::NaiveDate, cfg: &config::Config, sources: &sources::Sources, schedule: &schedule::Schedule, rules: &jsonl::SessionRules, debug: bool) {
     use colored::*;
     
-    let cfg = config::load_config();
-    let tracked_path = &cfg.projects.tracked_path;
     
-    let shift_type = schedule::get_shift_type(date);
-    let shift_name = match shift_type {
-        schedule::ShiftType::Regular => "REGULARNA",
-        schedule::ShiftType::Afternoon => "POPOŁUDNIOWA",
-        schedule::ShiftType::Weekend => "WEEKEND",
-        schedule::ShiftType::SaturdayAfternoon => "SOBOTA (zmiana popołudniowa)",
-    };
+    let shift_type = schedule.get_shift_type(date);
+    let shift_name = shift_type.label.to_uppercase();
     
-    let window = schedule::get_regular_work_window(date);
-    let window_desc = match &window {
-        Some(w) => format!("{}:00-{}:00 = regularne, reszta = nadgodziny", 
-            w.start.format("%H"), w.end.format("%H")),
-        None => "cały dzień = nadgodziny".to_string(),
+    let window_desc = if shift_type.windows.is_empty() {
+        "cały dzień = nadgodziny".to_string()
+    } else {
+        let windows: Vec<String> = shift_type.windows
+            .iter()
+            .map(|w| {
+                let next_day = if w.crosses_midnight() { " (następnego dnia)" } else { "" };
+                format!("{}-{}{}", w.start.format("%H:%M"), w.end.format("%H:%M"), next_day)
+            })
+            .collect();
+        format!("{} = regularne, reszta = nadgodziny", windows.join(", "))
     };
     
     println!();
     println!("{}", format!("[WYJAŚNIENIE dla {}]", date).cyan().bold());
     println!("Typ zmiany: {}", shift_name.yellow());
+    if let Some(holiday) = schedule.holiday_name(date) {
+        println!("Święto: {}", holiday);
+    }
+    if schedule.is_imported(date) {
+        println!("Zmiana z zaimportowanego grafiku (.ics)");
+    }
+    if let Some(day_override) = schedule.override_for(date) {
+        let note = day_override.note.as_deref().map(|n| format!(" - {}", n)).unwrap_or_default();
+        println!("{}", format!("Nadpisanie z overrides.json: {}{}", day_override.shift.label, note).magenta());
+    }
     println!("Okno pracy: {}", window_desc);
+    println!("Progi sesji: {}", describe_thresholds(&rules.default));
     println!();
     
-    let sessions = jsonl::load_sessions_for_date(date, debug);
+    let sessions = jsonl::load_sessions_for_date(date, sources, schedule, rules, debug);
     
     if sessions.is_empty() {
         println!("{}", "Brak sesji z nadgodzinami dla tego dnia.".red());
@@ -139,15 +323,16 @@ fn print_explain(date: chrono::NaiveDate, debug: bool) {
     let mut total_overtime_secs: f64 = 0.0;
     
     for (i, session) in sessions.iter().enumerate() {
-        let start_local = session.start_time.and_utc().with_timezone(&Warsaw).naive_local();
-        let end_local = session.end_time.and_utc().with_timezone(&Warsaw).naive_local();
+        let start_local = schedule.to_local(session.start_time);
+        let end_local = schedule.to_local(session.end_time);
         
-        let overtime_result = overtime::calculate_session_overtime(session, date, false);
+        let overtime_result = overtime::calculate_session_overtime(session, schedule, false);
         let overtime_hours = overtime_result.get(&date).copied().unwrap_or(0.0);
         let overtime_secs = overtime_hours * 3600.0;
         total_overtime_secs += overtime_secs;
         
         let duration_mins = session.duration_seconds / 60;
+        let estimated_mins = session.estimated_seconds / 60;
         let overtime_mins = (overtime_secs / 60.0).round() as i64;
         
         println!("{}. {} → {}", 
@@ -156,28 +341,36 @@ fn print_explain(date: chrono::NaiveDate, debug: bool) {
             end_local.format("%H:%M:%S").to_string().white()
         );
         
-        let real_projects: Vec<_> = session.project_counts
-            .iter()
-            .filter(|(name, _)| *name != "transcripts")
-            .collect();
-        
-        let total_real_records: usize = real_projects.iter().map(|(_, c)| *c).sum();
+        // Shares of the day's overtime, or of the whole session without any.
+        let mut intervals = overtime::day_blocks(&overtime::overtime_blocks(session, schedule), date);
+        if intervals.is_empty() {
+            intervals = session.segments.clone();
+        }
+        let real_projects = session.project_weights(cfg.projects.attribution, &intervals);
+        let total_weight: f64 = real_projects.values().sum();
         
         if real_projects.is_empty() {
             println!("   Projekty: {}", "(brak - tylko transcripts)".dimmed());
         } else {
-            println!("   Projekty:");
-            let mut sorted_projects: Vec<_> = real_projects.clone();
-            sorted_projects.sort_by(|a, b| b.1.cmp(a.1));
+            let method = match cfg.projects.attribution {
+                config::Attribution::Records => "wg liczby wpisów",
+                config::Attribution::Time => "wg czasu",
+            };
+            println!("   Projekty ({}):", method);
+            // Several paths (subdirectories, an alias) can share one name.
+            let mut by_name: HashMap<String, f64> = HashMap::new();
+            for (proj_path, weight) in &real_projects {
+                *by_name.entry(rules.projects.display(proj_path)).or_insert(0.0) += weight;
+            }
+            let mut sorted_projects: Vec<_> = by_name.into_iter().collect();
+            sorted_projects.sort_by(|a, b| b.1.total_cmp(&a.1));
             
-            for (proj_name, count) in &sorted_projects {
-                let count = **count;
-                let display_name = report::normalize_project_name(proj_name, tracked_path);
-                let pct = if total_real_records > 0 {
-                    (count as f64 / total_real_records as f64 * 100.0).round() as i64
+            for (display_name, weight) in sorted_projects {
+                let pct = if total_weight > 0.0 {
+                    (weight / total_weight * 100.0).round() as i64
                 } else { 0 };
-                let proj_overtime_mins = if total_real_records > 0 {
-                    (overtime_mins as f64 * count as f64 / total_real_records as f64).round() as i64
+                let proj_overtime_mins = if total_weight > 0.0 {
+                    (overtime_mins as f64 * weight / total_weight).round() as i64
                 } else { 0 };
                 let h = proj_overtime_mins / 60;
                 let m = proj_overtime_mins % 60;
@@ -191,7 +384,52 @@ fn print_explain(date: chrono::NaiveDate, debug: bool) {
             }
         }
         
-        println!("   Czas trwania: {} min", duration_mins);
+        if session.estimated_seconds > 0 {
+            println!("   Czas trwania: {} min zmierzone + {} min szacowane = {} min",
+                duration_mins, estimated_mins,
+                (session.duration_seconds + session.estimated_seconds) / 60);
+        } else {
+            println!("   Czas trwania: {} min", duration_mins);
+        }
+        if let Some(activity) = describe_activity(&session.stats) {
+            println!("   Aktywność: {}", activity);
+        }
+        if session.stats.sources.keys().any(|s| *s != "claude") {
+            let sources: Vec<_> = session
+                .stats
+                .sources
+                .iter()
+                .map(|(name, count)| format!("{} ({})", sources::source_label(name), count))
+                .collect();
+            println!("   Źródła: {}", sources.join(", "));
+        }
+        if !session.stats.branches.is_empty() {
+            let branches: Vec<_> = session.stats.branches.iter().map(String::as_str).collect();
+            println!("   Gałęzie: {}", branches.join(", "));
+        }
+        if !session.stats.models.is_empty() {
+            let models: Vec<_> = session.stats.models.iter().map(String::as_str).collect();
+            println!("   Modele: {}", models.join(", ").dimmed());
+        }
+        if session.agent_driven {
+            println!("   {}", format!("Sesja sub-agenta: {} zdarzeń poza Twoimi sesjami (kategoria Agenci)", session.subagent_events).magenta());
+        } else if session.subagent_events > 0 {
+            let counted = match rules.subagents {
+                config::SubagentPolicy::Ignore => "nieliczone",
+                config::SubagentPolicy::Merge => "wliczone do sesji",
+                config::SubagentPolicy::Separate => "poza sesją liczone osobno",
+            };
+            println!("   Sub-agenci: {} zdarzeń ({})", session.subagent_events, counted);
+        }
+        if session.segments.len() > 1 {
+            let span = (session.end_time - session.start_time).num_seconds();
+            let idle_mins = (span - session.duration_seconds - session.estimated_seconds) / 60;
+            println!("   Aktywne odcinki: {} (nieliczona bezczynność: {} min)", session.segments.len(), idle_mins);
+        }
+        if session.thresholds != rules.default {
+            let project = rules.projects.display(&session.project);
+            println!("   Progi sesji ({}): {}", project.cyan(), describe_thresholds(&session.thresholds));
+        }
         
         if overtime_mins > 0 {
             let h = overtime_mins / 60;
@@ -0,0 +1,92 @@
+use chrono::{Duration, NaiveDate};
+use serde::Deserialize;
+use std::fs;
+
+use crate::config::config_file_path;
+use crate::schedule::WorkWindow;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaveKind {
+    Vacation,
+    Sick,
+}
+
+impl LeaveKind {
+    /// Name of the built-in shift type used for this kind of leave.
+    pub fn shift_name(&self) -> &'static str {
+        match self {
+            LeaveKind::Vacation => "vacation",
+            LeaveKind::Sick => "sick",
+        }
+    }
+}
+
+/// One entry of `overrides.json`: a single `date` or a `from`..`to` range
+/// (inclusive), given exactly one of `shift`, `leave` or `windows`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OverrideEntry {
+    pub date: Option<NaiveDate>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub shift: Option<String>,
+    pub leave: Option<LeaveKind>,
+    pub windows: Option<Vec<WorkWindow>>,
+    pub note: Option<String>,
+}
+
+pub enum OverrideKind<'a> {
+    Shift(&'a str),
+    Leave(LeaveKind),
+    Windows(&'a [WorkWindow]),
+}
+
+impl OverrideEntry {
+    pub fn dates(&self) -> Result<Vec<NaiveDate>, String> {
+        let (from, to) = match (self.date, self.from, self.to) {
+            (Some(date), None, None) => (date, date),
+            (None, Some(from), Some(to)) if from <= to => (from, to),
+            _ => return Err("Nadpisanie musi mieć pole \"date\" albo \"from\" i \"to\" (from <= to)".to_string()),
+        };
+
+        let mut dates = Vec::new();
+        let mut date = from;
+        while date <= to {
+            dates.push(date);
+            date += Duration::days(1);
+        }
+        Ok(dates)
+    }
+
+    pub fn kind(&self) -> Result<OverrideKind<'_>, String> {
+        match (&self.shift, self.leave, &self.windows) {
+            (Some(shift), None, None) => Ok(OverrideKind::Shift(shift)),
+            (None, Some(leave), None) => Ok(OverrideKind::Leave(leave)),
+            (None, None, Some(windows)) => Ok(OverrideKind::Windows(windows)),
+            _ => Err("Nadpisanie musi mieć dokładnie jedno z pól \"shift\", \"leave\" lub \"windows\"".to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct OverridesFile {
+    #[serde(default)]
+    pub overrides: Vec<OverrideEntry>,
+}
+
+pub fn load_overrides() -> Result<Vec<OverrideEntry>, String> {
+    let Some(path) = config_file_path("overrides.json") else {
+        return Ok(Vec::new());
+    };
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Nie można odczytać {}: {}", path.display(), e))?;
+    let file: OverridesFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Nieprawidłowy plik {}: {}", path.display(), e))?;
+
+    Ok(file.overrides)
+}
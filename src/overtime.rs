@@ -1,84 +1,132 @@
-use chrono::{NaiveDate, NaiveTime, Duration};
-use chrono_tz::Europe::Warsaw;
+use chrono::{NaiveDate, NaiveDateTime, Duration, SubsecRound};
 use std::collections::HashMap;
 
-use crate::schedule::{get_shift_type, get_regular_work_window, ShiftType};
+use crate::schedule::Schedule;
 use crate::jsonl::Session;
 
-pub fn calculate_session_overtime(session: &Session, _filter_date: NaiveDate, debug: bool) -> HashMap<NaiveDate, f64> {
-    let mut daily: HashMap<NaiveDate, f64> = HashMap::new();
-    
-    let start_utc = session.start_time;
-    let end_utc = session.end_time;
-    
-    let start_local = start_utc.and_utc().with_timezone(&Warsaw).naive_local();
-    let end_local = end_utc.and_utc().with_timezone(&Warsaw).naive_local();
-    
-    let mut current_date = start_local.date();
-    let end_date = end_local.date();
+/// Overtime parts of a session's active segments as UTC instants, split at
+/// local midnight and tagged with the local day they count towards. Working on
+/// instants keeps durations right on days with a DST transition (23 or 25 hours long).
+///
+/// Days are half-open `[00:00, next 00:00)`, so the blocks tile the overtime
+/// part of the session exactly. Segment bounds are truncated to whole seconds.
+pub fn overtime_blocks(session: &Session, schedule: &Schedule) -> Vec<(NaiveDate, NaiveDateTime, NaiveDateTime)> {
+    session
+        .segments
+        .iter()
+        .flat_map(|(start, end)| segment_blocks(schedule, start.trunc_subsecs(0), end.trunc_subsecs(0)))
+        .collect()
+}
+
+fn segment_blocks(schedule: &Schedule, start_utc: NaiveDateTime, end_utc: NaiveDateTime) -> Vec<(NaiveDate, NaiveDateTime, NaiveDateTime)> {
+    let mut blocks = Vec::new();
+    let mut current_date = schedule.to_local(start_utc).date();
+    let end_date = schedule.to_local(end_utc).date();
     
     while current_date <= end_date {
-        let day_start = current_date.and_hms_opt(0, 0, 0).unwrap();
-        let day_end = current_date.and_hms_opt(23, 59, 59).unwrap();
+        let next_date = current_date + Duration::days(1);
+        let day_start = schedule.to_utc(current_date.and_hms_opt(0, 0, 0).unwrap());
+        let day_end = schedule.to_utc(next_date.and_hms_opt(0, 0, 0).unwrap());
         
-        let block_start = start_local.max(day_start);
-        let block_end = end_local.min(day_end);
+        let block_start = start_utc.max(day_start);
+        let block_end = end_utc.min(day_end);
         
         if block_end > block_start {
-            let overtime_seconds = calculate_overtime_for_day(
-                current_date,
-                block_start.time(),
-                block_end.time(),
-            );
-            
-            if overtime_seconds > 0.0 {
-                let hours = overtime_seconds / 3600.0;
-                *daily.entry(current_date).or_insert(0.0) += hours;
-                
-                if debug {
-                    eprintln!("[DEBUG] {} overtime: {:.2}h", current_date, hours);
-                }
+            for (start, end) in subtract_regular(schedule, block_start, block_end) {
+                blocks.push((current_date, start, end));
             }
         }
         
-        current_date += Duration::days(1);
+        current_date = next_date;
+    }
+    
+    blocks
+}
+
+/// Intervals of `blocks` (see `overtime_blocks`) that count towards `date`.
+pub fn day_blocks(blocks: &[(NaiveDate, NaiveDateTime, NaiveDateTime)], date: NaiveDate) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    blocks
+        .iter()
+        .filter(|(day, _, _)| *day == date)
+        .map(|(_, start, end)| (*start, *end))
+        .collect()
+}
+
+/// Overtime seconds per local day.
+pub fn session_overtime_seconds(session: &Session, schedule: &Schedule) -> HashMap<NaiveDate, i64> {
+    let mut daily: HashMap<NaiveDate, i64> = HashMap::new();
+    
+    for (date, start, end) in overtime_blocks(session, schedule) {
+        *daily.entry(date).or_insert(0) += (end - start).num_seconds();
     }
     
     daily
 }
 
-fn calculate_overtime_for_day(date: NaiveDate, start: NaiveTime, end: NaiveTime) -> f64 {
-    let shift_type = get_shift_type(date);
+pub fn calculate_session_overtime(session: &Session, schedule: &Schedule, debug: bool) -> HashMap<NaiveDate, f64> {
+    let daily: HashMap<NaiveDate, f64> = session_overtime_seconds(session, schedule)
+        .into_iter()
+        .map(|(date, seconds)| (date, seconds as f64 / 3600.0))
+        .collect();
     
-    match shift_type {
-        ShiftType::Weekend => {
-            (end - start).num_seconds() as f64
+    if debug {
+        for (date, hours) in &daily {
+            eprintln!("[DEBUG] {} overtime: {:.2}h", date, hours);
         }
-        ShiftType::Regular | ShiftType::Afternoon | ShiftType::SaturdayAfternoon => {
-            if let Some(window) = get_regular_work_window(date) {
-                let mut overtime_secs = 0.0;
-                
-                if start < window.start {
-                    let overtime_end = end.min(window.start);
-                    overtime_secs += (overtime_end - start).num_seconds() as f64;
-                }
-                
-                if end > window.end {
-                    let overtime_start = start.max(window.end);
-                    overtime_secs += (end - overtime_start).num_seconds() as f64;
-                }
-                
-                overtime_secs
-            } else {
-                (end - start).num_seconds() as f64
-            }
+    }
+    
+    daily
+}
+
+/// Parts of the UTC range `[start, end)` that fall outside regular work intervals.
+fn subtract_regular(schedule: &Schedule, start: NaiveDateTime, end: NaiveDateTime) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let local_start = schedule.to_local(start);
+    let local_end = schedule.to_local(end);
+    
+    // Around a fall-back transition the local end can precede the local start.
+    let mut regular: Vec<_> = schedule
+        .regular_intervals(local_start.min(local_end), local_start.max(local_end))
+        .into_iter()
+        .map(|(s, e)| (schedule.to_utc(s), schedule.to_utc(e)))
+        .collect();
+    regular.sort();
+    
+    let mut parts = Vec::new();
+    let mut cursor = start;
+    
+    for (window_start, window_end) in regular {
+        if window_start > cursor {
+            parts.push((cursor, window_start.min(end)));
         }
+        cursor = cursor.max(window_end);
+        if cursor >= end {
+            break;
+        }
+    }
+    
+    if cursor < end {
+        parts.push((cursor, end));
     }
+    
+    parts
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::NaiveTime;
+    use crate::timezone::TimeZones;
+    
+    /// Tests are written against Warsaw time, whatever the system zone.
+    fn warsaw(schedule: Schedule) -> Schedule {
+        schedule.with_time_zones(TimeZones::fixed(chrono_tz::Europe::Warsaw))
+    }
+    
+    /// Overtime hours of a session from `start` to `end` local time on `date`.
+    fn overtime_hours(schedule: &Schedule, date: NaiveDate, start: NaiveTime, end: NaiveTime) -> f64 {
+        let session = Session::spanning(schedule.to_utc(date.and_time(start)), schedule.to_utc(date.and_time(end)));
+        calculate_session_overtime(&session, schedule, false).values().sum()
+    }
     
     #[test]
     fn test_regular_day_no_overtime() {
@@ -86,7 +134,7 @@ mod tests {
         let start = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
         let end = NaiveTime::from_hms_opt(14, 0, 0).unwrap();
         
-        let overtime = calculate_overtime_for_day(date, start, end);
+        let overtime = overtime_hours(&warsaw(Schedule::default()), date, start, end);
         assert_eq!(overtime, 0.0);
     }
     
@@ -96,8 +144,8 @@ mod tests {
         let start = NaiveTime::from_hms_opt(14, 0, 0).unwrap();
         let end = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
         
-        let overtime = calculate_overtime_for_day(date, start, end);
-        assert_eq!(overtime, 2.0 * 3600.0);
+        let overtime = overtime_hours(&warsaw(Schedule::default()), date, start, end);
+        assert_eq!(overtime, 2.0);
     }
     
     #[test]
@@ -106,8 +154,8 @@ mod tests {
         let start = NaiveTime::from_hms_opt(10, 0, 0).unwrap();
         let end = NaiveTime::from_hms_opt(14, 0, 0).unwrap();
         
-        let overtime = calculate_overtime_for_day(date, start, end);
-        assert_eq!(overtime, 4.0 * 3600.0);
+        let overtime = overtime_hours(&warsaw(Schedule::default()), date, start, end);
+        assert_eq!(overtime, 4.0);
     }
     
     #[test]
@@ -116,7 +164,132 @@ mod tests {
         let start = NaiveTime::from_hms_opt(10, 0, 0).unwrap();
         let end = NaiveTime::from_hms_opt(14, 0, 0).unwrap();
         
-        let overtime = calculate_overtime_for_day(date, start, end);
-        assert_eq!(overtime, 4.0 * 3600.0);
+        let overtime = overtime_hours(&warsaw(Schedule::default()), date, start, end);
+        assert_eq!(overtime, 4.0);
+    }
+    
+    #[test]
+    fn test_split_shift_windows() {
+        let config: crate::config::ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2026-01-05",
+            "shift_types": [{"name": "split", "label": "Dzielona", "windows": [
+                {"start": "06:00", "end": "10:00"},
+                {"start": "14:00", "end": "18:00"}
+            ]}],
+            "rotation": [{"day": "split"}]
+        }"#).unwrap();
+        let schedule = warsaw(Schedule::from_config(&config).unwrap());
+        let date = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let start = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let end = NaiveTime::from_hms_opt(15, 0, 0).unwrap();
+        
+        let overtime = overtime_hours(&schedule, date, start, end);
+        assert_eq!(overtime, 4.0);
+    }
+    
+    #[test]
+    fn test_night_shift_across_midnight_is_regular() {
+        let config: crate::config::ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2026-01-05",
+            "shift_types": [{"name": "night", "label": "Noc", "windows": [{"start": "22:00", "end": "06:00"}]}],
+            "rotation": [{"day": "night"}]
+        }"#).unwrap();
+        let schedule = warsaw(Schedule::from_config(&config).unwrap());
+        
+        // 23:00-02:00 Warsaw time (UTC+1 in January)
+        let start = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap().and_hms_opt(22, 0, 0).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 1, 6).unwrap().and_hms_opt(1, 0, 0).unwrap();
+        let overtime = calculate_session_overtime(&Session::spanning(start, end), &schedule, false);
+        assert!(overtime.values().all(|h| *h == 0.0));
+        
+        // Staying until 07:00 local leaves one hour of overtime on the next day.
+        let late_end = NaiveDate::from_ymd_opt(2026, 1, 6).unwrap().and_hms_opt(6, 0, 0).unwrap();
+        let overtime = calculate_session_overtime(&Session::spanning(start, late_end), &schedule, false);
+        assert_eq!(overtime.get(&late_end.date()).copied(), Some(1.0));
+    }
+    
+    #[test]
+    fn test_fall_back_session_counts_real_hours() {
+        // Sunday 2025-10-26, 01:00 CEST - 04:00 CET: four real hours, all weekend overtime.
+        let start = NaiveDate::from_ymd_opt(2025, 10, 25).unwrap().and_hms_opt(23, 0, 0).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 10, 26).unwrap().and_hms_opt(3, 0, 0).unwrap();
+        
+        let overtime = calculate_session_overtime(&Session::spanning(start, end), &warsaw(Schedule::default()), false);
+        let date = NaiveDate::from_ymd_opt(2025, 10, 26).unwrap();
+        assert_eq!(overtime.get(&date).copied(), Some(4.0));
+    }
+    
+    #[test]
+    fn test_spring_forward_session_counts_real_hours() {
+        // Sunday 2026-03-29, 01:00 CET - 04:00 CEST: only two real hours.
+        let start = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap().and_hms_opt(2, 0, 0).unwrap();
+        
+        let overtime = calculate_session_overtime(&Session::spanning(start, end), &warsaw(Schedule::default()), false);
+        assert_eq!(overtime.get(&start.date()).copied(), Some(2.0));
+    }
+    
+    #[test]
+    fn test_regular_window_on_dst_day() {
+        let config: crate::config::ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2025-10-20",
+            "shift_types": [{"name": "night", "label": "Noc", "windows": [{"start": "00:00", "end": "06:00"}]}],
+            "rotation": [{"day": "night"}]
+        }"#).unwrap();
+        let schedule = warsaw(Schedule::from_config(&config).unwrap());
+        
+        // 00:00-07:00 local on 2025-10-26 lasts 8 real hours; 00:00-06:00 (7h) is regular.
+        let start = NaiveDate::from_ymd_opt(2025, 10, 25).unwrap().and_hms_opt(22, 0, 0).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 10, 26).unwrap().and_hms_opt(6, 0, 0).unwrap();
+        
+        let overtime = calculate_session_overtime(&Session::spanning(start, end), &schedule, false);
+        assert_eq!(overtime.get(&end.date()).copied(), Some(1.0));
+    }
+    
+    #[test]
+    fn test_last_second_of_day_is_counted() {
+        // Sunday 2025-08-10, 23:00 - Monday 01:00 local: no second lost at midnight.
+        let start = NaiveDate::from_ymd_opt(2025, 8, 10).unwrap().and_hms_opt(21, 0, 0).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 8, 10).unwrap().and_hms_opt(23, 0, 0).unwrap();
+        
+        let overtime = session_overtime_seconds(&Session::spanning(start, end), &warsaw(Schedule::default()));
+        assert_eq!(overtime.get(&start.date()).copied(), Some(3600));
+        assert_eq!(overtime.get(&end.date().succ_opt().unwrap()).copied(), Some(3600));
+    }
+    
+    fn night_schedule() -> Schedule {
+        let config: crate::config::ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2025-10-20",
+            "shift_types": [{"name": "night", "label": "Noc", "windows": [{"start": "22:00", "end": "06:00"}]}],
+            "rotation": [{"day": "night"}, {"day": "regular"}, {"day": "weekend"}]
+        }"#).unwrap();
+        warsaw(Schedule::from_config(&config).unwrap())
+    }
+    
+    proptest::proptest! {
+        #[test]
+        fn prop_day_parts_sum_to_session_overtime(
+            offset in 0i64..(400 * 86_400),
+            length in 0i64..(4 * 86_400),
+            nanos in 0u32..1_000_000_000,
+            night in proptest::bool::ANY,
+        ) {
+            let schedule = if night { night_schedule() } else { warsaw(Schedule::default()) };
+            let base = NaiveDate::from_ymd_opt(2025, 7, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+            let start = base + Duration::seconds(offset) + Duration::nanoseconds(nanos as i64);
+            let session = Session::spanning(start, start + Duration::seconds(length));
+            
+            let blocks = overtime_blocks(&session, &schedule);
+            for pair in blocks.windows(2) {
+                proptest::prop_assert!(pair[0].2 <= pair[1].1);
+            }
+            
+            let total: i64 = subtract_regular(&schedule, start.trunc_subsecs(0), session.end_time.trunc_subsecs(0))
+                .iter()
+                .map(|(s, e)| (*e - *s).num_seconds())
+                .sum();
+            let per_day: i64 = session_overtime_seconds(&session, &schedule).values().sum();
+            proptest::prop_assert_eq!(per_day, total);
+        }
     }
 }
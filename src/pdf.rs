@@ -8,7 +8,8 @@ use std::path::PathBuf;
 
 use crate::config::Config;
 use crate::jsonl::ProjectHours;
-use crate::report::normalize_project_name;
+use crate::projects::ProjectResolver;
+use crate::timezone::TimeZones;
 
 const FONT_DIRS: &[&str] = &[
     "/usr/share/fonts/liberation",
@@ -33,13 +34,15 @@ const MARGIN: f32 = 20.0;
 pub fn generate_pdf(
     daily_projects: &HashMap<NaiveDate, HashMap<String, ProjectHours>>,
     config: &Config,
+    resolver: &ProjectResolver,
+    zones: &TimeZones,
     month_filter: Option<&str>,
 ) -> Result<PathBuf, String> {
-    let (month_name, year, filtered_dates) = get_month_info(daily_projects, month_filter)?;
-    let project_totals = calculate_project_totals(daily_projects, &filtered_dates, config);
+    let (month_name, year, filtered_dates) = get_month_info(daily_projects, month_filter, zones.today())?;
+    let project_totals = calculate_project_totals(daily_projects, &filtered_dates, config, resolver);
 
     let (doc, page1, layer1) = PdfDocument::new(
-        &format!("Raport nadgodzin - {} {}", month_name, year),
+        format!("Raport nadgodzin - {} {}", month_name, year),
         Mm(PAGE_W),
         Mm(PAGE_H),
         "Layer 1",
@@ -67,7 +70,7 @@ pub fn generate_pdf(
     // Title
     layer.set_fill_color(Color::Rgb(Rgb::new(WHITE.0, WHITE.1, WHITE.2, None)));
     layer.use_text(
-        &format!("RAPORT NADGODZIN"),
+        "RAPORT NADGODZIN",
         24.0,
         Mm(MARGIN + 10.0),
         Mm(y - 15.0),
@@ -76,7 +79,7 @@ pub fn generate_pdf(
 
     // Month/Year
     layer.use_text(
-        &format!("{} {}", month_name.to_uppercase(), year),
+        format!("{} {}", month_name.to_uppercase(), year),
         14.0,
         Mm(PAGE_W - MARGIN - 60.0),
         Mm(y - 15.0),
@@ -129,7 +132,6 @@ pub fn generate_pdf(
     y -= row_height;
 
     // Calculate totals and rates
-    let tracked_path = &config.projects.tracked_path;
     let hourly_weekday = config.salary.base_monthly_net / config.salary.hours_per_month
         * config.salary.overtime_multiplier_weekday;
     let hourly_weekend = config.salary.base_monthly_net / config.salary.hours_per_month
@@ -156,7 +158,7 @@ pub fn generate_pdf(
     // Table rows
     let mut row_idx = 0;
     for (proj_name, hours) in &sorted_projects {
-        let display_name = normalize_project_name(proj_name, tracked_path);
+        let display_name = resolver.display(proj_name);
         let total_hours = hours.weekday_hours + hours.weekend_hours;
 
         if total_hours < 0.01 {
@@ -187,7 +189,7 @@ pub fn generate_pdf(
             )));
             let mut x = table_x + 3.0;
             layer.use_text(
-                &truncate(&display_name, 28),
+                truncate(&display_name, 28),
                 9.0,
                 Mm(x),
                 Mm(y - 5.5),
@@ -195,7 +197,7 @@ pub fn generate_pdf(
             );
             x += col_widths[0];
             layer.use_text(
-                &format_hours(hours.weekday_hours),
+                format_hours(hours.weekday_hours),
                 9.0,
                 Mm(x),
                 Mm(y - 5.5),
@@ -205,7 +207,7 @@ pub fn generate_pdf(
             layer.use_text("dzien", 9.0, Mm(x), Mm(y - 5.5), &font_regular);
             x += col_widths[2];
             layer.use_text(
-                &format!("{:.0}", pln),
+                format!("{:.0}", pln),
                 9.0,
                 Mm(x),
                 Mm(y - 5.5),
@@ -213,7 +215,7 @@ pub fn generate_pdf(
             );
             x += col_widths[3];
             layer.use_text(
-                &format!("{:.0}%", pct),
+                format!("{:.0}%", pct),
                 9.0,
                 Mm(x),
                 Mm(y - 5.5),
@@ -253,10 +255,10 @@ pub fn generate_pdf(
                 None,
             )));
             let mut x = table_x + 3.0;
-            layer.use_text(&truncate(&name, 28), 9.0, Mm(x), Mm(y - 5.5), &font_regular);
+            layer.use_text(truncate(&name, 28), 9.0, Mm(x), Mm(y - 5.5), &font_regular);
             x += col_widths[0];
             layer.use_text(
-                &format_hours(hours.weekend_hours),
+                format_hours(hours.weekend_hours),
                 9.0,
                 Mm(x),
                 Mm(y - 5.5),
@@ -273,7 +275,7 @@ pub fn generate_pdf(
             )));
             x += col_widths[2];
             layer.use_text(
-                &format!("{:.0}", pln),
+                format!("{:.0}", pln),
                 9.0,
                 Mm(x),
                 Mm(y - 5.5),
@@ -281,7 +283,7 @@ pub fn generate_pdf(
             );
             x += col_widths[3];
             layer.use_text(
-                &format!("{:.0}%", pct),
+                format!("{:.0}%", pct),
                 9.0,
                 Mm(x),
                 Mm(y - 5.5),
@@ -309,7 +311,7 @@ pub fn generate_pdf(
     layer.use_text("SUMA", 10.0, Mm(x), Mm(y - 6.0), &font_bold);
     x += col_widths[0];
     layer.use_text(
-        &format_hours(grand_total_hours),
+        format_hours(grand_total_hours),
         10.0,
         Mm(x),
         Mm(y - 6.0),
@@ -318,7 +320,7 @@ pub fn generate_pdf(
     x += col_widths[1];
     x += col_widths[2];
     layer.use_text(
-        &format!("{:.0} PLN", grand_total_pln),
+        format!("{:.0} PLN", grand_total_pln),
         10.0,
         Mm(x),
         Mm(y - 6.0),
@@ -329,7 +331,7 @@ pub fn generate_pdf(
 
     layer.set_fill_color(Color::Rgb(Rgb::new(0.5, 0.5, 0.5, None)));
     layer.use_text(
-        &format!(
+        format!(
             "Stawka netto: {:.0} PLN/h (dzien), {:.0} PLN/h (weekend)",
             hourly_weekday, hourly_weekend
         ),
@@ -348,9 +350,9 @@ pub fn generate_pdf(
     );
     y -= 4.0;
     layer.use_text(
-        &format!(
+        format!(
             "Wygenerowano: {}",
-            chrono::Local::now().format("%Y-%m-%d %H:%M")
+            zones.to_local(chrono::Utc::now().naive_utc()).format("%Y-%m-%d %H:%M")
         ),
         8.0,
         Mm(MARGIN),
@@ -407,6 +409,7 @@ fn load_font(doc: &PdfDocumentReference, filename: &str) -> Result<IndirectFontR
 fn get_month_info(
     daily_projects: &HashMap<NaiveDate, HashMap<String, ProjectHours>>,
     month_filter: Option<&str>,
+    today: NaiveDate,
 ) -> Result<(String, i32, Vec<NaiveDate>), String> {
     let filtered_dates: Vec<NaiveDate> = if let Some(filter) = month_filter {
         let parts: Vec<&str> = filter.split('-').collect();
@@ -422,7 +425,6 @@ fn get_month_info(
             .copied()
             .collect()
     } else {
-        let today = chrono::Local::now().date_naive();
         daily_projects
             .keys()
             .filter(|d| d.year() == today.year() && d.month() == today.month())
@@ -445,13 +447,14 @@ fn calculate_project_totals(
     daily_projects: &HashMap<NaiveDate, HashMap<String, ProjectHours>>,
     filtered_dates: &[NaiveDate],
     config: &Config,
+    resolver: &ProjectResolver,
 ) -> HashMap<String, ProjectHours> {
     let mut totals: HashMap<String, ProjectHours> = HashMap::new();
 
     for date in filtered_dates {
         if let Some(day_projects) = daily_projects.get(date) {
             for (proj_name, hours) in day_projects {
-                let normalized = normalize_project_name(proj_name, &config.projects.tracked_path);
+                let normalized = resolver.display(proj_name);
 
                 if config.projects.excluded_projects.contains(&normalized) {
                     continue;
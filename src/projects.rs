@@ -0,0 +1,455 @@
+use regex::{Captures, Regex};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{GitIdentity, ProjectRule, ProjectsConfig};
+use crate::git;
+use crate::jsonl::AGENT_PROJECT;
+
+/// Marks a project key that is a repository's remote identity.
+const REMOTE_PREFIX: &str = "git:";
+
+/// A compiled way of finding a project in a path.
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// User rule: a regex (or a glob turned into one) and a name template
+    /// with `$1` / `${name}` references to its capture groups.
+    Pattern { regex: Regex, template: String },
+    /// The directory right below `prefix` is the project. Roots must start
+    /// the path; the legacy `tracked_path` fragment may appear anywhere.
+    Root { prefix: String, anchored: bool },
+    /// A repository listed in `sources.git.repos`: its top-level directory
+    /// and everything below is the project `name`, tracked or not.
+    Repo { toplevel: String, name: String },
+}
+
+/// Maps paths seen in the logs to project names: ordered rules first, then
+/// tracked roots, then `tracked_path`; aliases are applied to the result.
+///
+/// Besides real paths it accepts the directory names of `~/.claude/projects`,
+/// which encode a path with `-` in place of `/`. Such a name is matched in its
+/// naively decoded form and captured text is taken from the original, so
+/// `-home-me-code-after15-core` under the root `/home/me/code` gives `after15-core`.
+///
+/// Remote identities (`github.com/me/farmaster2`, see [`GitIdentity`]) are
+/// keyed with a `git:` prefix, so they cannot be mistaken for relative paths,
+/// and named after their last component unless a rule or alias says otherwise.
+#[derive(Debug, Clone)]
+pub struct ProjectResolver {
+    matchers: Vec<Matcher>,
+    aliases: HashMap<String, String>,
+    git_identity: GitIdentity,
+    repo_cache: RefCell<HashMap<String, Option<String>>>,
+    probe_cache: RefCell<HashMap<String, Option<PathBuf>>>,
+}
+
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => format!("{}/{}", home.display(), rest),
+        _ => path.to_string(),
+    }
+}
+
+/// Anchored regex for a glob; every `*`, `**` and `?` becomes a capture group,
+/// numbered from the left.
+pub fn glob_to_regex(glob: &str) -> String {
+    let glob = expand_home(glob);
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str("(.*)");
+            }
+            '*' => regex.push_str("([^/]*)"),
+            '?' => regex.push_str("([^/])"),
+            other => regex.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// Expands `$1`, `${1}` and `${name}` with text of `original` at the
+/// positions the groups matched.
+fn expand_template(template: &str, caps: &Captures, original: &str) -> String {
+    let group = |key: &str| {
+        key.parse::<usize>()
+            .ok()
+            .and_then(|i| caps.get(i))
+            .or_else(|| caps.name(key))
+            .map(|m| &original[m.range()])
+            .unwrap_or("")
+    };
+
+    let mut result = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+        let mut key = String::new();
+        if chars.peek() == Some(&'{') {
+            chars.next();
+            for k in chars.by_ref() {
+                if k == '}' {
+                    break;
+                }
+                key.push(k);
+            }
+        } else {
+            while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                key.push(*d);
+                chars.next();
+            }
+        }
+        if key.is_empty() {
+            result.push('$');
+        } else {
+            result.push_str(group(&key));
+        }
+    }
+    result
+}
+
+impl Matcher {
+    fn from_rule(rule: &ProjectRule) -> Result<Self, String> {
+        let pattern = match (&rule.glob, &rule.regex) {
+            (Some(glob), None) => glob_to_regex(glob),
+            (None, Some(regex)) => regex.clone(),
+            _ => return Err(format!("Reguła projektu \"{}\": podaj dokładnie jedno z pól glob/regex", rule.name)),
+        };
+        let regex = Regex::new(&pattern)
+            .map_err(|e| format!("Reguła projektu \"{}\": nieprawidłowy wzorzec: {}", rule.name, e))?;
+        Ok(Matcher::Pattern { regex, template: rule.name.clone() })
+    }
+
+    /// `path` is matched; names are read from `original`, which has the same
+    /// length (it differs only for encoded directory names).
+    fn apply(&self, path: &str, original: &str, encoded: bool) -> Option<String> {
+        match self {
+            Matcher::Pattern { regex, template } => {
+                let caps = regex.captures(path)?;
+                Some(expand_template(template, &caps, original))
+            }
+            Matcher::Root { prefix, anchored } => {
+                let marker = format!("{}/", prefix);
+                let start = if *anchored {
+                    path.starts_with(&marker).then_some(marker.len())?
+                } else {
+                    path.find(&marker)? + marker.len()
+                };
+                let rest = &original[start..];
+                // An encoded name cannot tell a `-` in the project name from a
+                // separator, so everything after the root is taken. It cannot
+                // keep `_` either, so real paths get `-` in its place too.
+                let name = if encoded { rest } else { rest.split('/').next()? };
+                Some(name.trim_matches('-').replace('_', "-"))
+            }
+            Matcher::Repo { toplevel, name } => {
+                let inside = path.strip_prefix(toplevel.as_str())?;
+                (inside.is_empty() || inside.starts_with('/')).then(|| name.clone())
+            }
+        }
+    }
+}
+
+impl ProjectResolver {
+    pub fn from_config(config: &ProjectsConfig) -> Result<Self, String> {
+        let mut matchers = config
+            .rules
+            .iter()
+            .map(Matcher::from_rule)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for root in &config.roots {
+            matchers.push(Matcher::Root {
+                prefix: expand_home(root).trim_end_matches('/').to_string(),
+                anchored: true,
+            });
+        }
+
+        if !config.tracked_path.is_empty() {
+            matchers.push(Matcher::Root {
+                prefix: format!("/{}", config.tracked_path.trim_matches('/')),
+                anchored: false,
+            });
+        }
+
+        Ok(Self {
+            matchers,
+            aliases: config.aliases.clone(),
+            git_identity: config.git_identity,
+            repo_cache: RefCell::new(HashMap::new()),
+            probe_cache: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Makes the repositories read by the git source projects of their own,
+    /// after all other matchers, so their commits never fall into "Inne".
+    pub fn with_repos(mut self, repos: &[String]) -> Self {
+        for repo in repos {
+            let path = PathBuf::from(expand_home(repo));
+            let toplevel = git::find_repo(&path).map(|r| r.toplevel).unwrap_or(path);
+            let Some(name) = toplevel.file_name().map(|n| n.to_string_lossy().replace('_', "-")) else { continue };
+            self.matchers.push(Matcher::Repo { toplevel: toplevel.to_string_lossy().to_string(), name });
+        }
+        self
+    }
+
+    fn alias(&self, name: String) -> String {
+        self.aliases.get(&name).cloned().unwrap_or(name)
+    }
+
+    /// Project for an absolute path, an encoded `~/.claude/projects` directory
+    /// name or a remote identity key; `None` when it is not a tracked project.
+    /// A bare name is accepted only as an alias, and a relative path never.
+    pub fn resolve(&self, raw: &str) -> Option<String> {
+        if raw.is_empty() || raw == "unknown" || raw == "transcripts" {
+            return None;
+        }
+
+        if let Some(identity) = raw.strip_prefix(REMOTE_PREFIX) {
+            if let Some(alias) = self.aliases.get(identity) {
+                return Some(alias.clone());
+            }
+            let name = self
+                .matchers
+                .iter()
+                .find_map(|m| m.apply(identity, identity, false))
+                .unwrap_or_else(|| identity.rsplit('/').next().unwrap_or(identity).to_string());
+            return Some(self.alias(name));
+        }
+
+        let encoded = raw.starts_with('-');
+        if !encoded && !raw.starts_with('/') {
+            return self.aliases.get(raw).cloned();
+        }
+
+        let path = if encoded { raw.replace('-', "/") } else { raw.to_string() };
+        self.matchers
+            .iter()
+            .find_map(|m| m.apply(&path, raw, encoded))
+            .filter(|name| !name.is_empty())
+            .map(|name| self.alias(name))
+    }
+
+    /// Key for a real path inside a git repository: the top-level directory
+    /// or the remote, per `git_identity`. `None` outside a repository, for
+    /// encoded names and when disabled; the caller keeps the path then. Only
+    /// a repository whose top-level directory is itself a tracked project
+    /// counts, so neither a dotfiles repository enclosing a tracked root nor
+    /// an untracked clone with a remote turns into a project.
+    pub fn repo_identity(&self, path: &str) -> Option<String> {
+        if self.git_identity == GitIdentity::Off || !path.starts_with('/') {
+            return None;
+        }
+        if let Some(cached) = self.repo_cache.borrow().get(path) {
+            return cached.clone();
+        }
+
+        let identity = git::find_repo(Path::new(path))
+            .filter(|repo| self.resolve(&repo.toplevel.to_string_lossy()).is_some())
+            .map(|repo| {
+                let remote = match self.git_identity {
+                    GitIdentity::Remote => repo.remote(),
+                    _ => None,
+                };
+                remote
+                    .map(|remote| format!("{}{}", REMOTE_PREFIX, remote))
+                    .unwrap_or_else(|| repo.toplevel.to_string_lossy().to_string())
+            });
+        self.repo_cache.borrow_mut().insert(path.to_string(), identity.clone());
+        identity
+    }
+
+    /// [`probe_project_dir`], probing the filesystem once per name.
+    pub fn probe_dir(&self, encoded: &str) -> Option<PathBuf> {
+        if let Some(cached) = self.probe_cache.borrow().get(encoded) {
+            return cached.clone();
+        }
+        let found = probe_project_dir(encoded);
+        self.probe_cache.borrow_mut().insert(encoded.to_string(), found.clone());
+        found
+    }
+
+    /// Project key for a path touched by a tool or command: its repository
+    /// identity or the path itself. `None` outside tracked projects, so
+    /// incidental paths (`/tmp`, dotfiles) do not create projects.
+    pub fn key_for_path(&self, path: &str) -> Option<String> {
+        let key = self.repo_identity(path).unwrap_or_else(|| path.to_string());
+        self.resolve(&key).map(|_| key)
+    }
+
+    /// Name shown in reports.
+    pub fn display(&self, raw: &str) -> String {
+        if raw == AGENT_PROJECT {
+            return "Agenci".to_string();
+        }
+        self.resolve(raw).unwrap_or_else(|| "Inne".to_string())
+    }
+}
+
+/// How Claude Code names a project directory after the working directory:
+/// every character other than an ASCII letter, digit or `-` becomes `-`.
+pub fn encode_project_dir(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+        .collect()
+}
+
+/// Deepest directory level `probe_project_dir` descends to.
+const MAX_PROBE_DEPTH: usize = 16;
+
+/// Recovers the path behind an encoded project directory name by walking the
+/// filesystem from `/`, preferring the longest directory name at each level
+/// (so `after15-core` wins over `after15/core`). `None` when nothing on disk
+/// encodes to it, e.g. the project was moved or deleted, or it would lie
+/// deeper than `MAX_PROBE_DEPTH` levels.
+pub fn probe_project_dir(encoded: &str) -> Option<PathBuf> {
+    probe_below(Path::new("/"), encoded.strip_prefix('-')?, MAX_PROBE_DEPTH)
+}
+
+fn probe_below(base: &Path, rest: &str, depth: usize) -> Option<PathBuf> {
+    if rest.is_empty() {
+        return Some(base.to_path_buf());
+    }
+    if depth == 0 {
+        return None;
+    }
+
+    let mut candidates: Vec<(String, String)> = fs::read_dir(base)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter_map(|name| {
+            let encoded = encode_project_dir(&name);
+            let remainder = rest.strip_prefix(encoded.as_str())?;
+            if remainder.is_empty() {
+                Some((name, String::new()))
+            } else {
+                remainder.strip_prefix('-').map(|r| (name, r.to_string()))
+            }
+        })
+        .collect();
+    candidates.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+    candidates
+        .into_iter()
+        .find_map(|(name, remainder)| probe_below(&base.join(name), &remainder, depth - 1))
+}
+
+impl Default for ProjectResolver {
+    fn default() -> Self {
+        Self::from_config(&ProjectsConfig::default()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn resolver(json: &str) -> ProjectResolver {
+        let config: ProjectsConfig = serde_json::from_str(json).unwrap();
+        ProjectResolver::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn test_tracked_path_compatibility() {
+        let projects = ProjectResolver::default();
+        assert_eq!(projects.display("-home-jarx-Programowanie-farmaster2"), "farmaster2");
+        assert_eq!(projects.display("-home-jarx-Programowanie-after15-core"), "after15-core");
+        assert_eq!(projects.display("/home/jarx/Programowanie/after15-core/src/main.rs"), "after15-core");
+        assert_eq!(projects.display("-home-jarx-Dokumenty"), "Inne");
+        assert_eq!(projects.display("farmaster2"), "Inne");
+        assert_eq!(projects.resolve("Programowanie/farmaster2/src/main.rs"), None);
+        
+        // Encoded names lose `_`, so paths are named the same way.
+        assert_eq!(projects.display("/home/jarx/Programowanie/farm_app/src/lib.rs"), "farm-app");
+        assert_eq!(projects.display("-home-jarx-Programowanie-farm-app"), "farm-app");
+    }
+
+    #[test]
+    fn test_rules_roots_and_aliases() {
+        let projects = resolver(r#"{
+            "tracked_path": "",
+            "roots": ["/srv/work"],
+            "rules": [
+                {"glob": "/home/*/clients/*/**", "name": "$2 ($1)"},
+                {"regex": "/opt/(?P<team>[a-z]+)/repos/(?P<repo>[^/]+)", "name": "${team}-${repo}"}
+            ],
+            "aliases": {"farmaster-v2": "farmaster2"}
+        }"#);
+
+        assert_eq!(projects.resolve("/home/ola/clients/acme/src/lib.rs").as_deref(), Some("acme (ola)"));
+        assert_eq!(projects.resolve("/opt/infra/repos/dns/zones").as_deref(), Some("infra-dns"));
+        assert_eq!(projects.resolve("/srv/work/farmaster-v2/Cargo.toml").as_deref(), Some("farmaster2"));
+        assert_eq!(projects.resolve("-srv-work-farmaster-v2").as_deref(), Some("farmaster2"));
+        assert_eq!(projects.resolve("farmaster-v2").as_deref(), Some("farmaster2"));
+        assert_eq!(projects.resolve("/home/jarx/Programowanie/x"), None);
+    }
+
+    #[test]
+    fn test_probe_project_dir() {
+        let root = TempDir::new("probe");
+        fs::create_dir_all(root.join("Programowanie/after15-core")).unwrap();
+        fs::create_dir_all(root.join("Programowanie/after15/core")).unwrap();
+        fs::create_dir_all(root.join(".config/my_tool")).unwrap();
+
+        let probe = |path: &Path| probe_project_dir(&encode_project_dir(&path.to_string_lossy()));
+        assert_eq!(probe(&root.join("Programowanie/after15-core")), Some(root.join("Programowanie/after15-core")));
+        assert_eq!(probe(&root.join(".config/my_tool")), Some(root.join(".config/my_tool")));
+        assert_eq!(probe(&root.join("missing")), None);
+        assert_eq!(probe_below(root.path(), "Programowanie-after15-core", 1), None);
+
+        // Cached: the answer stays after the directory is gone.
+        let projects = ProjectResolver::default();
+        let encoded = encode_project_dir(&root.join("Programowanie/after15-core").to_string_lossy());
+        assert!(projects.probe_dir(&encoded).is_some());
+        fs::remove_dir_all(root.join("Programowanie/after15-core")).unwrap();
+        assert!(projects.probe_dir(&encoded).is_some());
+    }
+
+    #[test]
+    fn test_remote_identity() {
+        let projects = resolver(r#"{
+            "tracked_path": "Programowanie",
+            "aliases": {"github.com/firma/fm2": "farmaster2"}
+        }"#);
+        assert_eq!(projects.display("git:github.com/jarx/after15"), "after15");
+        assert_eq!(projects.display("git:github.com/firma/fm2"), "farmaster2");
+        assert_eq!(projects.display("github.com/jarx/after15"), "Inne");
+    }
+
+    #[test]
+    fn test_repo_identity_needs_tracked_toplevel() {
+        let home = TempDir::new("identity");
+        let remote = |name: &str| format!("[remote \"origin\"]\n\turl = git@github.com:jarx/{}.git\n", name);
+        for repo in ["", "code/lib", "other/clone"] {
+            home.write(Path::new(repo).join(".git/config"), remote(repo.rsplit('/').next().unwrap()));
+        }
+        fs::create_dir_all(home.join("code/app/src")).unwrap();
+
+        let projects = resolver(&format!(r#"{{"tracked_path": "", "roots": ["{}/code"], "git_identity": "remote"}}"#, home.path().display()));
+        let identity = |path: &str| projects.repo_identity(&home.join(path).to_string_lossy());
+        assert_eq!(identity("code/lib/src").as_deref(), Some("git:github.com/jarx/lib"));
+        // Inside the dotfiles repository at "home", above the tracked root.
+        assert_eq!(identity("code/app/src"), None);
+        assert_eq!(identity("other/clone"), None);
+    }
+
+    #[test]
+    fn test_invalid_rule() {
+        let config: ProjectsConfig = serde_json::from_str(r#"{
+            "tracked_path": "x",
+            "rules": [{"regex": "([", "name": "$1"}]
+        }"#).unwrap();
+        assert!(ProjectResolver::from_config(&config).is_err());
+    }
+}
@@ -1,14 +1,15 @@
-use chrono::{Datelike, Local, NaiveDate};
+use chrono::{Datelike, NaiveDate};
 use colored::*;
 use std::collections::HashMap;
 use tabled::{
-    settings::{object::Columns, Alignment, Modify, Style},
+    settings::{format::Format, object::Columns, Alignment, Modify, Style},
     Table, Tabled,
 };
 
 use crate::config::Config;
 use crate::jsonl::ProjectHours;
-use crate::schedule::{get_shift_type, ShiftType};
+use crate::projects::ProjectResolver;
+use crate::schedule::{Schedule, ShiftType};
 
 #[derive(Clone)]
 pub struct DayReport {
@@ -22,9 +23,11 @@ pub fn print_full_report(
     daily: &HashMap<NaiveDate, f64>,
     projects: &HashMap<NaiveDate, HashMap<String, ProjectHours>>,
     config: &Config,
+    schedule: &Schedule,
+    resolver: &ProjectResolver,
     month_filter: Option<&str>,
 ) {
-    let today = Local::now().date_naive();
+    let today = schedule.today();
 
     let filtered_daily: HashMap<NaiveDate, f64> = if let Some(filter) = month_filter {
         daily
@@ -45,7 +48,7 @@ pub fn print_full_report(
         .map(|(date, hours)| DayReport {
             date: *date,
             hours: *hours,
-            shift_type: get_shift_type(*date),
+            shift_type: schedule.get_shift_type(*date).clone(),
             from_daily_summary: *date != today,
         })
         .collect();
@@ -124,7 +127,7 @@ pub fn print_full_report(
             projects.clone()
         };
 
-    print_project_tables(&filtered_daily, &filtered_projects, config, month_filter);
+    print_project_tables(&filtered_daily, &filtered_projects, config, resolver, month_filter);
 }
 
 fn print_daily_table(days: &[DayReport]) {
@@ -180,7 +183,7 @@ fn print_monthly_stats(daily: &HashMap<NaiveDate, f64>) {
     }
 
     let mut months: Vec<_> = monthly.iter().collect();
-    months.sort_by(|(a, _), (b, _)| a.cmp(b));
+    months.sort_by_key(|(a, _)| *a);
 
     for (month, hours) in months {
         let hours_str = format!(
@@ -210,8 +213,7 @@ fn print_summary_stats(daily: &HashMap<NaiveDate, f64>) {
 
     let max_day = daily
         .iter()
-        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-        .map(|(d, h)| (d, h));
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap());
 
     println!("  📅 Dni z nadgodzinami: {}", days_with_overtime);
     println!("  📈 Średnia dzienna: {}", format_hm(avg_hours));
@@ -225,6 +227,7 @@ fn print_project_tables(
     _daily: &HashMap<NaiveDate, f64>,
     projects: &HashMap<NaiveDate, HashMap<String, ProjectHours>>,
     config: &Config,
+    resolver: &ProjectResolver,
     month_filter: Option<&str>,
 ) {
     let mut monthly_projects: HashMap<String, HashMap<String, ProjectHours>> = HashMap::new();
@@ -235,7 +238,7 @@ fn print_project_tables(
         let month_entry = monthly_projects.entry(month_key.clone()).or_default();
 
         for (project, hours) in day_projects {
-            let normalized = normalize_project_name(project, &config.projects.tracked_path);
+            let normalized = resolver.display(project);
 
             if config.projects.excluded_projects.contains(&normalized) {
                 continue;
@@ -330,49 +333,123 @@ fn print_project_tables(
     }
 }
 
-pub fn normalize_project_name(raw_name: &str, tracked_path: &str) -> String {
-    if raw_name.is_empty() {
-        return "Inne".to_string();
+/// Overtime of one project per directory (`--by-path`).
+pub fn print_path_breakdown(
+    project: &str,
+    depth: usize,
+    month: &str,
+    rows: &HashMap<String, ProjectHours>,
+    config: &Config,
+) {
+    let total: f64 = rows.values().map(|h| h.weekday_hours + h.weekend_hours).sum();
+
+    println!();
+    println!(
+        "{}",
+        format!("📂 {} wg katalogów (głębokość {}) - {} (nadgodzin: {}):", project, depth, month, format_hm(total))
+            .cyan()
+            .bold()
+    );
+    println!();
+
+    if rows.is_empty() {
+        println!("{}", "Brak nadgodzin tego projektu w wybranym miesiącu.".red());
+        return;
     }
 
-    if raw_name.contains(tracked_path) {
-        let pattern = format!("-home-jarx-{}-", tracked_path);
-        let name = raw_name.replace(&pattern, "");
-        let name = name.trim_matches('-');
-        if name.is_empty() {
-            "Inne".to_string()
-        } else {
-            name.to_string()
-        }
-    } else {
-        "Inne".to_string()
+    let mut sorted: Vec<_> = rows.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    print_hours_table("Katalog", sorted, config);
+}
+
+pub fn print_tool_breakdown(month: &str, rows: &HashMap<String, ProjectHours>, config: &Config) {
+    let total: f64 = rows.values().map(|h| h.weekday_hours + h.weekend_hours).sum();
+
+    println!();
+    println!(
+        "{}",
+        format!("🛠️  NARZĘDZIA - {} (nadgodzin: {}):", month, format_hm(total)).cyan().bold()
+    );
+    println!();
+
+    if rows.is_empty() {
+        println!("{}", "Brak nadgodzin w wybranym miesiącu.".red());
+        return;
     }
+
+    let mut sorted: Vec<_> = rows.iter().collect();
+    sorted.sort_by(|a, b| {
+        let total = |h: &ProjectHours| h.weekday_hours + h.weekend_hours;
+        total(b.1).total_cmp(&total(a.1))
+    });
+    print_hours_table("Narzędzie", sorted, config);
 }
 
-fn get_day_emoji(shift_type: &ShiftType) -> &'static str {
-    match shift_type {
-        ShiftType::Weekend => "🏠",
-        ShiftType::SaturdayAfternoon => "📅",
-        ShiftType::Afternoon => "🌆",
-        ShiftType::Regular => "🏢",
+/// Table of weekday/weekend hours and pay per row, in the given order.
+fn print_hours_table(column: &str, rows: Vec<(&String, &ProjectHours)>, config: &Config) {
+    #[derive(Tabled)]
+    struct HoursRow {
+        name: String,
+        #[tabled(rename = "Dzień")]
+        weekday: String,
+        #[tabled(rename = "Wknd")]
+        weekend: String,
+        #[tabled(rename = "Suma")]
+        total: String,
+        #[tabled(rename = "PLN")]
+        pln: String,
     }
+
+    let hourly_weekday = config.overtime_rate_weekday();
+    let hourly_weekend = config.overtime_rate_weekend();
+
+    let table_rows: Vec<HoursRow> = rows
+        .into_iter()
+        .map(|(name, hours)| HoursRow {
+            name: name.clone(),
+            weekday: format_hm(hours.weekday_hours),
+            weekend: format_hm(hours.weekend_hours),
+            total: format_hm(hours.weekday_hours + hours.weekend_hours),
+            pln: format!(
+                "{:.0} PLN",
+                hours.weekday_hours * hourly_weekday + hours.weekend_hours * hourly_weekend
+            ),
+        })
+        .collect();
+
+    let table = Table::new(table_rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::new(1..=4)).with(Alignment::right()))
+        .with(Modify::new((0, 0)).with(Format::content(|_| column.to_string())))
+        .to_string();
+
+    println!("{}", table);
+    println!();
+}
+
+fn get_day_emoji(shift_type: &ShiftType) -> &str {
+    &shift_type.emoji
 }
 
 fn shift_type_name(shift_type: &ShiftType) -> String {
-    match shift_type {
-        ShiftType::Weekend => "Weekend".to_string(),
-        ShiftType::SaturdayAfternoon => "Sobota".to_string(),
-        ShiftType::Afternoon => "Popołudnie".to_string(),
-        ShiftType::Regular => "Normalny".to_string(),
-    }
+    shift_type.label.clone()
 }
 
 fn overtime_window(shift_type: &ShiftType) -> String {
-    match shift_type {
-        ShiftType::Weekend => "cały dzień".to_string(),
-        ShiftType::SaturdayAfternoon => "przed 8:00 i po 14:00".to_string(),
-        ShiftType::Afternoon => "przed 15:00 i po 21:00".to_string(),
-        ShiftType::Regular => "przed 6:00 i po 15:00".to_string(),
+    let fmt = |t: &chrono::NaiveTime| t.format("%-H:%M").to_string();
+
+    match shift_type.windows.as_slice() {
+        [] => "cały dzień".to_string(),
+        [window] if !window.crosses_midnight() => {
+            format!("przed {} i po {}", fmt(&window.start), fmt(&window.end))
+        }
+        windows => {
+            let ranges: Vec<String> = windows
+                .iter()
+                .map(|w| format!("{}-{}", fmt(&w.start), fmt(&w.end)))
+                .collect();
+            format!("poza {}", ranges.join(", "))
+        }
     }
 }
 
@@ -0,0 +1,218 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ics::{parse_events, IcsEvent, IcsTime};
+use crate::schedule::{Schedule, WorkWindow};
+use crate::timezone::TimeZones;
+
+/// A day taken from an imported rota. `windows` comes from the event times;
+/// `None` (all-day event) means the shift type's own windows.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ImportedDay {
+    pub shift: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub windows: Option<Vec<WorkWindow>>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ImportedSchedule {
+    #[serde(default)]
+    pub days: BTreeMap<NaiveDate, ImportedDay>,
+}
+
+pub struct ImportSummary {
+    pub events: usize,
+    pub days: usize,
+    pub unmapped: BTreeSet<String>,
+    pub path: PathBuf,
+}
+
+fn get_imported_path() -> Option<PathBuf> {
+    dirs::data_dir()
+        .or_else(|| dirs::home_dir().map(|p| p.join(".local/share")))
+        .map(|p| p.join("claude-overtime/imported_schedule.json"))
+}
+
+pub fn load_imported_schedule() -> Result<ImportedSchedule, String> {
+    let Some(path) = get_imported_path() else {
+        return Ok(ImportedSchedule::default());
+    };
+
+    if !path.exists() {
+        return Ok(ImportedSchedule::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Nie można odczytać {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Nieprawidłowy plik {}: {}", path.display(), e))
+}
+
+fn save_imported_schedule(imported: &ImportedSchedule) -> Result<PathBuf, String> {
+    let Some(path) = get_imported_path() else {
+        return Err("Nie można ustalić katalogu danych".to_string());
+    };
+    let write_error = |e: std::io::Error| format!("Nie można zapisać {}: {}", path.display(), e);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(write_error)?;
+    }
+
+    let tmp_path = path.with_extension("json.tmp");
+    let content = serde_json::to_string_pretty(imported).map_err(|e| e.to_string())?;
+
+    fs::write(&tmp_path, content).map_err(write_error)?;
+    fs::rename(&tmp_path, &path).map_err(write_error)?;
+
+    Ok(path)
+}
+
+/// Shift type name for an event summary: first the `ics_mapping` config,
+/// then a shift type whose name or label equals the summary.
+fn resolve_shift(summary: &str, mapping: &HashMap<String, String>, schedule: &Schedule) -> Option<String> {
+    let summary = summary.trim();
+    let wanted = summary.to_lowercase();
+
+    mapping
+        .iter()
+        .find(|(key, _)| key.trim().to_lowercase() == wanted)
+        .map(|(_, shift)| shift.clone())
+        .or_else(|| schedule.find_shift_type(summary).map(|t| t.name.clone()))
+}
+
+/// Turns events into per-date shifts. Timed events become work windows on the
+/// day they start; events whose summary has no mapping are returned separately.
+/// Timed events take precedence over an all-day event on the same date (the
+/// first timed event names the shift), and the first all-day event wins over
+/// later ones, whatever the order of events in the file.
+pub fn days_from_events(
+    events: &[IcsEvent],
+    mapping: &HashMap<String, String>,
+    schedule: &Schedule,
+    zones: &TimeZones,
+) -> (BTreeMap<NaiveDate, ImportedDay>, BTreeSet<String>) {
+    let mut days: BTreeMap<NaiveDate, ImportedDay> = BTreeMap::new();
+    let mut unmapped = BTreeSet::new();
+
+    for event in events {
+        let Some(shift) = resolve_shift(&event.summary, mapping, schedule) else {
+            unmapped.insert(event.summary.trim().to_string());
+            continue;
+        };
+
+        let tz = zones.zone_for_date(event.start.nominal_date());
+        let start = event.start.to_local(&tz);
+        let end = event.end.as_ref().and_then(|e| e.to_local(&tz));
+
+        match (start, end) {
+            (Some(start), Some(end)) if end > start && end - start < Duration::days(1) => {
+                let window = WorkWindow { start: start.time(), end: end.time() };
+                let day = days.entry(start.date()).or_insert_with(|| ImportedDay {
+                    shift: shift.clone(),
+                    windows: None,
+                });
+                if day.windows.is_none() {
+                    day.shift = shift;
+                }
+                day.windows.get_or_insert_with(Vec::new).push(window);
+            }
+            _ => {
+                let date = match (&event.start, start) {
+                    (IcsTime::Date(date), _) => *date,
+                    (_, Some(start)) => start.date(),
+                    _ => continue,
+                };
+                days.entry(date).or_insert(ImportedDay { shift, windows: None });
+            }
+        }
+    }
+
+    (days, unmapped)
+}
+
+/// Imports a rota from an .ics file, merging it into the stored schedule;
+/// dates present in the file replace previously imported ones.
+pub fn import_ics(
+    file: &Path,
+    mapping: &HashMap<String, String>,
+    schedule: &Schedule,
+) -> Result<ImportSummary, String> {
+    let content = fs::read_to_string(file)
+        .map_err(|e| format!("Nie można odczytać {}: {}", file.display(), e))?;
+    let events = parse_events(&content);
+    if events.is_empty() {
+        return Err(format!("Brak wydarzeń (VEVENT) w pliku {}", file.display()));
+    }
+
+    let (days, unmapped) = days_from_events(&events, mapping, schedule, schedule.zones());
+
+    // A broken stored rota must not block importing a fresh one.
+    let mut imported = load_imported_schedule().unwrap_or_else(|e| {
+        eprintln!("[UWAGA] {} - zostanie nadpisany", e);
+        ImportedSchedule::default()
+    });
+    let day_count = days.len();
+    imported.days.extend(days);
+    let path = save_imported_schedule(&imported)?;
+
+    Ok(ImportSummary {
+        events: events.len(),
+        days: day_count,
+        unmapped,
+        path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    const ROTA: &str = "BEGIN:VCALENDAR\n\
+BEGIN:VEVENT\nSUMMARY:N\nDTSTART;TZID=Europe/Warsaw:20260105T220000\nDTEND;TZID=Europe/Warsaw:20260106T060000\nEND:VEVENT\n\
+BEGIN:VEVENT\nSUMMARY:Popołudnie\nDTSTART;TZID=Europe/Warsaw:20260107T140000\nDTEND;TZID=Europe/Warsaw:20260107T220000\nEND:VEVENT\n\
+BEGIN:VEVENT\nSUMMARY:weekend\nDTSTART;VALUE=DATE:20260108\nEND:VEVENT\n\
+BEGIN:VEVENT\nSUMMARY:Szkolenie BHP\nDTSTART:20260109T080000Z\nDTEND:20260109T100000Z\nEND:VEVENT\n\
+END:VCALENDAR\n";
+
+    #[test]
+    fn test_days_from_events() {
+        let mapping = HashMap::from([("n".to_string(), "regular".to_string())]);
+        let events = parse_events(ROTA);
+        let (days, unmapped) = days_from_events(&events, &mapping, &Schedule::default(), &TimeZones::fixed(chrono_tz::Europe::Warsaw));
+
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+
+        let night = &days[&date(5)];
+        assert_eq!(night.shift, "regular");
+        assert_eq!(night.windows, Some(vec![WorkWindow { start: time(22), end: time(6) }]));
+
+        // Matched by shift type label.
+        assert_eq!(days[&date(7)].shift, "afternoon");
+        assert_eq!(days[&date(8)].shift, "weekend");
+        assert!(days[&date(8)].windows.is_none());
+
+        assert_eq!(unmapped, BTreeSet::from(["Szkolenie BHP".to_string()]));
+    }
+
+    #[test]
+    fn test_timed_event_wins_over_all_day() {
+        let timed = "BEGIN:VEVENT\nSUMMARY:afternoon\nDTSTART;TZID=Europe/Warsaw:20260112T140000\nDTEND;TZID=Europe/Warsaw:20260112T220000\nEND:VEVENT\n";
+        let all_day = "BEGIN:VEVENT\nSUMMARY:weekend\nDTSTART;VALUE=DATE:20260112\nEND:VEVENT\n";
+        let zones = TimeZones::fixed(chrono_tz::Europe::Warsaw);
+        let date = NaiveDate::from_ymd_opt(2026, 1, 12).unwrap();
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+
+        for (first, second) in [(timed, all_day), (all_day, timed)] {
+            let calendar = format!("BEGIN:VCALENDAR\n{}{}END:VCALENDAR\n", first, second);
+            let (days, _) = days_from_events(&parse_events(&calendar), &HashMap::new(), &Schedule::default(), &zones);
+
+            assert_eq!(days[&date].shift, "afternoon");
+            assert_eq!(days[&date].windows, Some(vec![WorkWindow { start: time(14), end: time(22) }]));
+        }
+    }
+}
@@ -1,137 +1,550 @@
-use chrono::{NaiveDate, NaiveTime, DateTime, Local, Datelike, Weekday, Duration};
-
-const FIRST_AFTERNOON_START: (i32, u32, u32) = (2025, 7, 28);
-const FIRST_AFTERNOON_END: (i32, u32, u32) = (2025, 8, 2);
-const CYCLE_LENGTH_DAYS: i64 = 21;
-
-pub fn is_afternoon_shift_period(date: NaiveDate) -> bool {
-    let first_start = NaiveDate::from_ymd_opt(
-        FIRST_AFTERNOON_START.0,
-        FIRST_AFTERNOON_START.1,
-        FIRST_AFTERNOON_START.2
-    ).unwrap();
-    let first_end = NaiveDate::from_ymd_opt(
-        FIRST_AFTERNOON_END.0,
-        FIRST_AFTERNOON_END.1,
-        FIRST_AFTERNOON_END.2
-    ).unwrap();
-    
-    let days_since_first = (date - first_start).num_days();
-    if days_since_first >= 0 {
-        let cycle_number = days_since_first / CYCLE_LENGTH_DAYS;
-        let cycle_start = first_start + Duration::days(cycle_number * CYCLE_LENGTH_DAYS);
-        let cycle_end = first_end + Duration::days(cycle_number * CYCLE_LENGTH_DAYS);
-        return date >= cycle_start && date <= cycle_end;
-    }
-    false
-}
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Datelike, Weekday, Duration};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::{RotationTemplate, ScheduleConfig};
+use crate::holidays::PolishHolidays;
+use crate::timezone::TimeZones;
+use crate::overrides::{OverrideEntry, OverrideKind};
+use crate::rota::ImportedSchedule;
 
 pub fn is_weekend(date: NaiveDate) -> bool {
     matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
 }
 
-pub fn is_saturday(date: NaiveDate) -> bool {
-    date.weekday() == Weekday::Sat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
 }
 
-pub fn is_saturday_regular_hours(date: NaiveDate) -> bool {
-    is_saturday(date) && is_afternoon_shift_period(date)
+impl WorkWindow {
+    fn new(start_hour: u32, end_hour: u32) -> Self {
+        Self {
+            start: NaiveTime::from_hms_opt(start_hour, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end_hour, 0, 0).unwrap(),
+        }
+    }
+
+    /// A window whose end is not after its start (e.g. 22:00-06:00) runs into
+    /// the next day; it still belongs to the shift of the day it starts on.
+    pub fn crosses_midnight(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn on(&self, date: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+        let end_date = if self.crosses_midnight() { date + Duration::days(1) } else { date };
+        (date.and_time(self.start), end_date.and_time(self.end))
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum ShiftType {
-    Regular,
-    Afternoon,
-    Weekend,
-    SaturdayAfternoon,
+/// A kind of working day. Time inside any of `windows` is regular work,
+/// everything else is overtime; no windows means the whole day is overtime.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ShiftType {
+    pub name: String,
+    #[serde(default = "default_emoji")]
+    pub emoji: String,
+    pub label: String,
+    #[serde(default)]
+    pub windows: Vec<WorkWindow>,
 }
 
-pub fn get_shift_type(date: NaiveDate) -> ShiftType {
-    if is_weekend(date) {
-        if is_saturday_regular_hours(date) {
-            ShiftType::SaturdayAfternoon
-        } else {
-            ShiftType::Weekend
-        }
-    } else if is_afternoon_shift_period(date) {
-        ShiftType::Afternoon
-    } else {
-        ShiftType::Regular
-    }
+fn default_emoji() -> String {
+    "📆".to_string()
 }
 
-pub struct WorkWindow {
-    pub start: NaiveTime,
-    pub end: NaiveTime,
+pub fn builtin_shift_types() -> Vec<ShiftType> {
+    let shift = |name: &str, emoji: &str, label: &str, windows| ShiftType {
+        name: name.to_string(),
+        emoji: emoji.to_string(),
+        label: label.to_string(),
+        windows,
+    };
+
+    vec![
+        shift("regular", "🏢", "Normalny", vec![WorkWindow::new(6, 15)]),
+        shift("afternoon", "🌆", "Popołudnie", vec![WorkWindow::new(15, 21)]),
+        shift("saturday_afternoon", "📅", "Sobota", vec![WorkWindow::new(8, 14)]),
+        shift("weekend", "🏠", "Weekend", vec![]),
+        shift("holiday", "🎉", "Święto", vec![]),
+        shift("vacation", "🌴", "Urlop", vec![]),
+        shift("sick", "🤒", "L4", vec![]),
+    ]
 }
 
-pub fn get_regular_work_window(date: NaiveDate) -> Option<WorkWindow> {
-    match get_shift_type(date) {
-        ShiftType::Regular => Some(WorkWindow {
-            start: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
-            end: NaiveTime::from_hms_opt(15, 0, 0).unwrap(),
-        }),
-        ShiftType::Afternoon => Some(WorkWindow {
-            start: NaiveTime::from_hms_opt(15, 0, 0).unwrap(),
-            end: NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
-        }),
-        ShiftType::SaturdayAfternoon => Some(WorkWindow {
-            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
-            end: NaiveTime::from_hms_opt(14, 0, 0).unwrap(),
-        }),
-        ShiftType::Weekend => None,
+fn type_index(types: &[ShiftType], name: &str) -> Result<usize, String> {
+    types
+        .iter()
+        .position(|t| t.name == name)
+        .ok_or_else(|| format!("Nieznany typ zmiany w harmonogramie: {}", name))
+}
+
+/// A day whose shift comes from `overrides.json` instead of the rotation.
+#[derive(Debug, Clone)]
+pub struct DayOverride {
+    pub shift: ShiftType,
+    pub note: Option<String>,
+}
+
+/// Shift rotation resolved from the `schedule` config section: one shift type
+/// per day of the cycle, starting at the anchor date. A day is resolved from,
+/// in order: personal overrides, the imported rota, public holidays and the
+/// rotation. Days before the anchor are regular weekdays and weekends.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    anchor: NaiveDate,
+    types: Vec<ShiftType>,
+    cycle: Vec<usize>,
+    regular: usize,
+    weekend: usize,
+    holiday: usize,
+    /// Polish holidays, when enabled.
+    polish_holidays: Option<PolishHolidays>,
+    extra_holidays: HashMap<NaiveDate, String>,
+    imported: HashMap<NaiveDate, ShiftType>,
+    overrides: HashMap<NaiveDate, DayOverride>,
+    zones: TimeZones,
+}
+
+impl Schedule {
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self, String> {
+        let mut types = builtin_shift_types();
+        for custom in &config.shift_types {
+            match types.iter_mut().find(|t| t.name == custom.name) {
+                Some(existing) => *existing = custom.clone(),
+                None => types.push(custom.clone()),
+            }
+        }
+
+        let mut cycle = Vec::new();
+        for template in &config.rotation {
+            match template {
+                RotationTemplate::Week(days) => {
+                    for name in days {
+                        cycle.push(type_index(&types, name)?);
+                    }
+                }
+                RotationTemplate::Day(name) => cycle.push(type_index(&types, name)?),
+            }
+        }
+
+        if cycle.is_empty() {
+            return Err("Harmonogram nie zawiera żadnych dni (schedule.rotation)".to_string());
+        }
+
+        let regular = type_index(&types, "regular")?;
+        let weekend = type_index(&types, "weekend")?;
+        let holiday = type_index(&types, "holiday")?;
+        let extra_holidays = config.holidays.extra
+            .iter()
+            .map(|h| (h.date, h.name.clone()))
+            .collect();
+
+        Ok(Self {
+            anchor: config.anchor,
+            types,
+            cycle,
+            regular,
+            weekend,
+            holiday,
+            polish_holidays: config.holidays.polish.then(PolishHolidays::default),
+            extra_holidays,
+            imported: HashMap::new(),
+            overrides: HashMap::new(),
+            zones: TimeZones::fixed(crate::timezone::system_zone()),
+        })
+    }
+
+    /// Applies days imported from an .ics rota; event times replace the shift
+    /// type's own windows.
+    pub fn with_imported(mut self, imported: &ImportedSchedule) -> Result<Self, String> {
+        for (date, day) in &imported.days {
+            let mut shift = self.types[type_index(&self.types, &day.shift)?].clone();
+            if let Some(windows) = &day.windows {
+                shift.windows = windows.clone();
+            }
+            self.imported.insert(*date, shift);
+        }
+        Ok(self)
+    }
+
+    pub fn is_imported(&self, date: NaiveDate) -> bool {
+        self.imported.contains_key(&date)
+    }
+
+    /// Looks up a shift type by name or label, ignoring case.
+    pub fn find_shift_type(&self, name_or_label: &str) -> Option<&ShiftType> {
+        let wanted = name_or_label.to_lowercase();
+        self.types
+            .iter()
+            .find(|t| t.name.to_lowercase() == wanted || t.label.to_lowercase() == wanted)
+    }
+
+    /// Applies entries from `overrides.json`; a later entry wins over an
+    /// earlier one for the same date.
+    pub fn with_overrides(mut self, entries: &[OverrideEntry]) -> Result<Self, String> {
+        for entry in entries {
+            let shift = match entry.kind()? {
+                OverrideKind::Shift(name) => self.types[type_index(&self.types, name)?].clone(),
+                OverrideKind::Leave(kind) => self.types[type_index(&self.types, kind.shift_name())?].clone(),
+                OverrideKind::Windows(windows) => ShiftType {
+                    name: "custom".to_string(),
+                    emoji: "✏️".to_string(),
+                    label: "Własne okno".to_string(),
+                    windows: windows.to_vec(),
+                },
+            };
+
+            for date in entry.dates()? {
+                self.overrides.insert(date, DayOverride {
+                    shift: shift.clone(),
+                    note: entry.note.clone(),
+                });
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn override_for(&self, date: NaiveDate) -> Option<&DayOverride> {
+        self.overrides.get(&date)
+    }
+
+    pub fn holiday_name(&self, date: NaiveDate) -> Option<&str> {
+        if let Some(name) = self.extra_holidays.get(&date) {
+            return Some(name);
+        }
+        self.polish_holidays.as_ref().and_then(|holidays| holidays.name(date))
+    }
+
+    /// Whether overtime on this day is paid at the weekend multiplier. Checked
+    /// in the order of `get_shift_type`: a holiday that an override or the
+    /// imported rota turns into a working day is paid like a workday.
+    pub fn is_weekend_rate(&self, date: NaiveDate) -> bool {
+        if is_weekend(date) {
+            return true;
+        }
+        let assigned = self.overrides.get(&date).map(|o| &o.shift).or_else(|| self.imported.get(&date));
+        match assigned {
+            Some(shift) if !shift.windows.is_empty() => false,
+            _ => self.holiday_name(date).is_some(),
+        }
+    }
+
+    pub fn get_shift_type(&self, date: NaiveDate) -> &ShiftType {
+        if let Some(day_override) = self.overrides.get(&date) {
+            return &day_override.shift;
+        }
+        if let Some(imported) = self.imported.get(&date) {
+            return imported;
+        }
+        if self.holiday_name(date).is_some() {
+            return &self.types[self.holiday];
+        }
+
+        if date < self.anchor {
+            return &self.types[if is_weekend(date) { self.weekend } else { self.regular }];
+        }
+
+        let days_since_anchor = (date - self.anchor).num_days();
+        let day_in_cycle = days_since_anchor as usize % self.cycle.len();
+        &self.types[self.cycle[day_in_cycle]]
+    }
+
+    pub fn get_regular_work_windows(&self, date: NaiveDate) -> &[WorkWindow] {
+        &self.get_shift_type(date).windows
+    }
+
+    /// Regular work intervals overlapping `[from, to)`, sorted and merged. Starts
+    /// a day early so that a night window from the previous day is included.
+    pub fn regular_intervals(&self, from: NaiveDateTime, to: NaiveDateTime) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+        let mut intervals: Vec<(NaiveDateTime, NaiveDateTime)> = Vec::new();
+
+        let mut date = from.date() - Duration::days(1);
+        while date <= to.date() {
+            for window in self.get_regular_work_windows(date) {
+                let (start, end) = window.on(date);
+                if start < to && end > from {
+                    intervals.push((start, end));
+                }
+            }
+            date += Duration::days(1);
+        }
+
+        intervals.sort();
+        let mut merged: Vec<(NaiveDateTime, NaiveDateTime)> = Vec::new();
+        for (start, end) in intervals {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged
+    }
+
+    /// Time zone(s) used to read log timestamps as local work time.
+    pub fn with_time_zones(mut self, zones: TimeZones) -> Self {
+        self.zones = zones;
+        self
+    }
+
+    pub fn to_local(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        self.zones.to_local(utc)
+    }
+
+    pub fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        self.zones.to_utc(local)
+    }
+
+    pub fn zones(&self) -> &TimeZones {
+        &self.zones
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.zones.today()
+    }
+
+    pub fn is_overtime_hour(&self, utc: NaiveDateTime) -> bool {
+        let now = self.to_local(utc);
+        !self
+            .regular_intervals(now, now + Duration::seconds(1))
+            .iter()
+            .any(|(start, end)| *start <= now && now < *end)
     }
 }
 
-pub fn is_overtime_hour(dt: DateTime<Local>) -> bool {
-    let date = dt.date_naive();
-    let time = dt.time();
-    
-    match get_regular_work_window(date) {
-        Some(window) => time < window.start || time >= window.end,
-        None => true,
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::from_config(&ScheduleConfig::default()).unwrap()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
+    fn shift_name(schedule: &Schedule, date: NaiveDate) -> &str {
+        &schedule.get_shift_type(date).name
+    }
+
     #[test]
     fn test_afternoon_shift_first_period() {
+        let schedule = Schedule::default();
         let mon = NaiveDate::from_ymd_opt(2025, 7, 28).unwrap();
         let sat = NaiveDate::from_ymd_opt(2025, 8, 2).unwrap();
         let sun = NaiveDate::from_ymd_opt(2025, 8, 3).unwrap();
-        
-        assert!(is_afternoon_shift_period(mon));
-        assert!(is_afternoon_shift_period(sat));
-        assert!(!is_afternoon_shift_period(sun));
+
+        assert_eq!(shift_name(&schedule, mon), "afternoon");
+        assert_eq!(shift_name(&schedule, sat), "saturday_afternoon");
+        assert_eq!(shift_name(&schedule, sun), "weekend");
     }
-    
+
     #[test]
     fn test_afternoon_shift_second_cycle() {
+        let schedule = Schedule::default();
         let second_cycle_start = NaiveDate::from_ymd_opt(2025, 8, 18).unwrap();
-        assert!(is_afternoon_shift_period(second_cycle_start));
+        assert_eq!(shift_name(&schedule, second_cycle_start), "afternoon");
     }
-    
+
     #[test]
     fn test_regular_week() {
+        let schedule = Schedule::default();
         let regular_day = NaiveDate::from_ymd_opt(2025, 8, 4).unwrap();
-        assert!(!is_afternoon_shift_period(regular_day));
-        assert_eq!(get_shift_type(regular_day), ShiftType::Regular);
+        assert_eq!(shift_name(&schedule, regular_day), "regular");
     }
-    
+
     #[test]
     fn test_weekend() {
+        let schedule = Schedule::default();
         let sunday = NaiveDate::from_ymd_opt(2025, 8, 10).unwrap();
         assert!(is_weekend(sunday));
-        assert_eq!(get_shift_type(sunday), ShiftType::Weekend);
+        assert_eq!(shift_name(&schedule, sunday), "weekend");
     }
-    
+
     #[test]
     fn test_saturday_during_afternoon_shift() {
+        let schedule = Schedule::default();
         let sat = NaiveDate::from_ymd_opt(2025, 8, 2).unwrap();
-        assert!(is_saturday_regular_hours(sat));
-        assert_eq!(get_shift_type(sat), ShiftType::SaturdayAfternoon);
+        assert_eq!(shift_name(&schedule, sat), "saturday_afternoon");
+    }
+
+    #[test]
+    fn test_rotation_from_config() {
+        let config: ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2026-01-05",
+            "rotation": [
+                {"day": "regular"},
+                {"day": "regular"},
+                {"day": "afternoon"},
+                {"day": "weekend"}
+            ]
+        }"#).unwrap();
+        let schedule = Schedule::from_config(&config).unwrap();
+
+        let date = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        assert_eq!(shift_name(&schedule, date(5)), "regular");
+        assert_eq!(shift_name(&schedule, date(7)), "afternoon");
+        assert_eq!(shift_name(&schedule, date(8)), "weekend");
+        assert_eq!(shift_name(&schedule, date(9)), "regular");
+        assert_eq!(shift_name(&schedule, date(4)), "weekend");
+
+        // Before the anchor the rotation does not apply.
+        let before = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        assert_eq!(shift_name(&schedule, before), "regular");
+    }
+
+    #[test]
+    fn test_before_anchor() {
+        let schedule = Schedule::default();
+        let afternoon_weekday = NaiveDate::from_ymd_opt(2025, 7, 21).unwrap();
+        let saturday = NaiveDate::from_ymd_opt(2025, 7, 26).unwrap();
+        assert_eq!(shift_name(&schedule, afternoon_weekday), "regular");
+        assert_eq!(shift_name(&schedule, saturday), "weekend");
+    }
+
+    #[test]
+    fn test_custom_shift_types() {
+        let config: ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2026-01-05",
+            "shift_types": [
+                {"name": "long", "emoji": "⏱️", "label": "12h", "windows": [{"start": "07:00", "end": "19:00"}]},
+                {"name": "split", "label": "Dzielona", "windows": [
+                    {"start": "06:00", "end": "10:00"},
+                    {"start": "14:00", "end": "18:00"}
+                ]}
+            ],
+            "rotation": [{"day": "long"}, {"day": "split"}]
+        }"#).unwrap();
+        let schedule = Schedule::from_config(&config).unwrap();
+
+        let long = schedule.get_shift_type(NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+        assert_eq!(long.label, "12h");
+        assert_eq!(long.windows, vec![WorkWindow::new(7, 19)]);
+
+        // 6 Jan (Trzech Króli) would be a split day, but the holiday wins.
+        let holiday = NaiveDate::from_ymd_opt(2026, 1, 6).unwrap();
+        assert_eq!(shift_name(&schedule, holiday), "holiday");
+        assert!(schedule.get_regular_work_windows(holiday).is_empty());
+
+        let split_day = NaiveDate::from_ymd_opt(2026, 1, 8).unwrap();
+        assert_eq!(schedule.get_regular_work_windows(split_day).len(), 2);
+        assert_eq!(schedule.get_shift_type(split_day).emoji, "📆");
+    }
+
+    #[test]
+    fn test_unknown_shift_type_in_rotation() {
+        let config: ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2026-01-05",
+            "rotation": [{"day": "nights"}]
+        }"#).unwrap();
+        assert!(Schedule::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_night_window_belongs_to_start_date() {
+        let config: ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2026-01-05",
+            "shift_types": [{"name": "night", "label": "Noc", "windows": [{"start": "22:00", "end": "06:00"}]}],
+            "rotation": [{"day": "night"}, {"day": "weekend"}]
+        }"#).unwrap();
+        let schedule = Schedule::from_config(&config).unwrap();
+        let at = |d, h| NaiveDate::from_ymd_opt(2026, 1, d).unwrap().and_hms_opt(h, 0, 0).unwrap();
+
+        // 6 Jan is a "weekend" day, but its first hours belong to the 5 Jan night shift.
+        let intervals = schedule.regular_intervals(at(6, 0), at(6, 12));
+        assert_eq!(intervals, vec![(at(5, 22), at(6, 6))]);
+        assert!(schedule.regular_intervals(at(6, 7), at(6, 21)).is_empty());
+    }
+
+    #[test]
+    fn test_holidays() {
+        let config: ScheduleConfig = serde_json::from_str(r#"{
+            "anchor": "2025-07-28",
+            "rotation": [{"day": "regular"}],
+            "holidays": {"extra": [{"date": "2026-05-04", "name": "Dzień firmowy"}]}
+        }"#).unwrap();
+        let schedule = Schedule::from_config(&config).unwrap();
+
+        let easter_monday = NaiveDate::from_ymd_opt(2026, 4, 6).unwrap();
+        assert_eq!(shift_name(&schedule, easter_monday), "holiday");
+        assert!(schedule.is_weekend_rate(easter_monday));
+        assert!(schedule.get_regular_work_windows(easter_monday).is_empty());
+
+        let company_day = NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+        assert_eq!(schedule.holiday_name(company_day), Some("Dzień firmowy"));
+        assert_eq!(shift_name(&schedule, company_day), "holiday");
+
+        let workday = NaiveDate::from_ymd_opt(2026, 5, 5).unwrap();
+        assert_eq!(shift_name(&schedule, workday), "regular");
+        assert!(!schedule.is_weekend_rate(workday));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let file: crate::overrides::OverridesFile = serde_json::from_str(r#"{
+            "overrides": [
+                {"date": "2025-08-04", "shift": "afternoon", "note": "zamiana"},
+                {"from": "2025-08-05", "to": "2025-08-07", "leave": "vacation"},
+                {"date": "2025-11-11", "windows": [{"start": "10:00", "end": "18:00"}]}
+            ]
+        }"#).unwrap();
+        let schedule = Schedule::default().with_overrides(&file.overrides).unwrap();
+        let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+
+        assert_eq!(shift_name(&schedule, date(8, 4)), "afternoon");
+        assert_eq!(schedule.override_for(date(8, 4)).unwrap().note.as_deref(), Some("zamiana"));
+        assert_eq!(shift_name(&schedule, date(8, 7)), "vacation");
+        assert_eq!(shift_name(&schedule, date(8, 8)), "regular");
+        assert!(schedule.override_for(date(8, 8)).is_none());
+
+        // An explicit window wins over the public holiday.
+        assert_eq!(shift_name(&schedule, date(11, 11)), "custom");
+        assert_eq!(schedule.get_regular_work_windows(date(11, 11)), &[WorkWindow::new(10, 18)]);
+    }
+
+    #[test]
+    fn test_override_rate() {
+        let file: crate::overrides::OverridesFile = serde_json::from_str(r#"{
+            "overrides": [
+                {"date": "2025-11-11", "shift": "regular"},
+                {"date": "2025-12-25", "leave": "vacation"},
+                {"date": "2025-08-09", "shift": "regular"}
+            ]
+        }"#).unwrap();
+        let schedule = Schedule::default().with_overrides(&file.overrides).unwrap();
+        let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+
+        // Working a regular shift on a holiday pays like a workday...
+        assert!(!schedule.is_weekend_rate(date(11, 11)));
+        // ...leave on a holiday keeps the holiday rate, and so does a Saturday.
+        assert!(schedule.is_weekend_rate(date(12, 25)));
+        assert!(schedule.is_weekend_rate(date(8, 9)));
+        assert!(schedule.is_weekend_rate(date(11, 1)));
+    }
+
+    #[test]
+    fn test_invalid_override() {
+        let file: crate::overrides::OverridesFile = serde_json::from_str(r#"{
+            "overrides": [{"date": "2025-08-04", "shift": "afternoon", "leave": "sick"}]
+        }"#).unwrap();
+        assert!(Schedule::default().with_overrides(&file.overrides).is_err());
+    }
+
+    #[test]
+    fn test_imported_days() {
+        let imported: ImportedSchedule = serde_json::from_str(r#"{
+            "days": {
+                "2025-08-04": {"shift": "afternoon", "windows": [{"start": "14:00", "end": "22:00"}]},
+                "2025-08-05": {"shift": "weekend"}
+            }
+        }"#).unwrap();
+        let schedule = Schedule::default().with_imported(&imported).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2025, 8, d).unwrap();
+
+        assert_eq!(shift_name(&schedule, date(4)), "afternoon");
+        assert_eq!(schedule.get_regular_work_windows(date(4)), &[WorkWindow::new(14, 22)]);
+        assert!(schedule.is_imported(date(5)));
+        assert!(schedule.get_regular_work_windows(date(5)).is_empty());
+        assert_eq!(shift_name(&schedule, date(6)), "regular");
     }
 }
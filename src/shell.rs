@@ -0,0 +1,239 @@
+use chrono::{DateTime, Duration, NaiveDateTime};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::ShellSourceConfig;
+use crate::git::normalize_path;
+use crate::jsonl::{EventKind, TimestampRecord};
+use crate::projects::{expand_home, ProjectResolver};
+use crate::sources::{ActivitySource, Scope};
+
+const DEFAULT_FILES: [&str; 3] = ["~/.zsh_history", "~/.bash_history", "~/.local/share/fish/fish_history"];
+
+/// One command from a history file.
+#[derive(Debug, Clone, PartialEq)]
+struct Command {
+    timestamp: NaiveDateTime,
+    line: String,
+    /// Paths fish recorded for the command.
+    paths: Vec<String>,
+}
+
+/// Commands from zsh `EXTENDED_HISTORY` (`: 1700000000:0;make`), bash with
+/// `HISTTIMEFORMAT` (`#1700000000` before each command) and fish
+/// (`- cmd: make` / `  when: 1700000000`). Lines without a time are skipped.
+fn parse_history(content: &str) -> Vec<Command> {
+    let time = |secs: &str| secs.trim().parse::<i64>().ok().and_then(|s| DateTime::from_timestamp(s, 0)).map(|t| t.naive_utc());
+
+    let mut commands: Vec<Command> = Vec::new();
+    let mut bash_time: Option<NaiveDateTime> = None;
+    let mut fish_cmd: Option<String> = None;
+    let mut in_fish_paths = false;
+
+    for line in content.lines() {
+        if let Some(rest) = line.strip_prefix(": ")
+            && let Some((meta, command)) = rest.split_once(';')
+            && let Some(timestamp) = meta.split(':').next().and_then(time)
+        {
+            commands.push(Command { timestamp, line: command.to_string(), paths: vec![] });
+        } else if let Some(secs) = line.strip_prefix('#')
+            && let Some(timestamp) = time(secs)
+        {
+            bash_time = Some(timestamp);
+        } else if let Some(cmd) = line.strip_prefix("- cmd: ") {
+            fish_cmd = Some(cmd.to_string());
+            in_fish_paths = false;
+        } else if let Some(secs) = line.strip_prefix("  when: ") {
+            if let (Some(cmd), Some(timestamp)) = (fish_cmd.take(), time(secs)) {
+                commands.push(Command { timestamp, line: cmd, paths: vec![] });
+            }
+        } else if line == "  paths:" {
+            in_fish_paths = true;
+        } else if in_fish_paths && let Some(path) = line.strip_prefix("    - ") {
+            if let Some(last) = commands.last_mut() {
+                last.paths.push(path.to_string());
+            }
+        } else if let Some(timestamp) = bash_time.take() {
+            commands.push(Command { timestamp, line: line.to_string(), paths: vec![] });
+        }
+    }
+
+    commands
+}
+
+/// Follows `cd` through a history to know where later commands ran.
+struct WorkingDir {
+    home: Option<PathBuf>,
+    current: Option<PathBuf>,
+    /// After a pause longer than this the directory is unknown again: the
+    /// next command most likely ran in a new shell.
+    gap: Duration,
+    last: Option<NaiveDateTime>,
+}
+
+impl WorkingDir {
+    fn new(home: Option<PathBuf>, gap: Duration) -> Self {
+        Self { home, current: None, gap, last: None }
+    }
+
+    fn advance(&mut self, timestamp: NaiveDateTime) {
+        if self.last.is_some_and(|last| timestamp - last > self.gap) {
+            self.current = None;
+        }
+        self.last = Some(timestamp);
+    }
+
+    /// `path` as an absolute path: `~` is expanded and relative paths are
+    /// taken from the tracked directory (`None` when it is unknown).
+    fn absolute(&self, path: &str) -> Option<PathBuf> {
+        let path = path.trim_matches(|c| c == '"' || c == '\'');
+        if path == "~" {
+            return self.home.clone();
+        }
+        let expanded = PathBuf::from(expand_home(path));
+        if expanded.is_absolute() {
+            Some(normalize_path(&expanded))
+        } else {
+            self.current.as_ref().map(|cwd| normalize_path(&cwd.join(expanded)))
+        }
+    }
+
+    fn change(&mut self, target: Option<&str>) {
+        self.current = match target {
+            None => self.home.clone(),
+            Some("-") => None,
+            Some(target) => self.absolute(target),
+        };
+    }
+}
+
+/// Project key of a command: the directory it ran in, else the first path
+/// argument inside a tracked project. Updates `cwd` for `cd` commands.
+fn command_project(command: &Command, cwd: &mut WorkingDir, projects: &ProjectResolver) -> Option<(String, String)> {
+    cwd.advance(command.timestamp);
+    let mut arguments: Vec<PathBuf> = command.paths.iter().filter_map(|p| cwd.absolute(p)).collect();
+
+    for part in command.line.split("&&").flat_map(|p| p.split(';')) {
+        let mut words = part.split_whitespace();
+        match words.next() {
+            Some("cd") | Some("pushd") => cwd.change(words.next()),
+            Some(_) => arguments.extend(
+                words
+                    .filter(|w| !w.starts_with('-') && (w.contains('/') || w.starts_with('~')))
+                    .filter_map(|w| cwd.absolute(w)),
+            ),
+            None => {}
+        }
+    }
+
+    cwd.current
+        .iter()
+        .chain(arguments.iter())
+        .map(|p| p.to_string_lossy().to_string())
+        .find_map(|p| projects.key_for_path(&p).map(|key| (key, p)))
+}
+
+/// Commands from timestamped shell history, tagged with the project of the
+/// directory they ran in or of their path arguments. Commands outside
+/// tracked projects are skipped.
+pub struct ShellSource {
+    files: Vec<PathBuf>,
+    gap: Duration,
+}
+
+impl ShellSource {
+    /// `gap_minutes` is the session gap (`sessions.gap_minutes` or `--gap-minutes`).
+    pub fn from_config(config: &ShellSourceConfig, gap_minutes: u32) -> Self {
+        let files: Vec<&str> = if config.files.is_empty() {
+            DEFAULT_FILES.to_vec()
+        } else {
+            config.files.iter().map(String::as_str).collect()
+        };
+        Self {
+            files: files.into_iter().map(|f| PathBuf::from(expand_home(f))).collect(),
+            gap: Duration::minutes(gap_minutes as i64),
+        }
+    }
+}
+
+impl ActivitySource for ShellSource {
+    fn name(&self) -> &'static str {
+        "shell"
+    }
+
+    fn records(&self, scope: Scope, projects: &ProjectResolver, debug: bool) -> Vec<TimestampRecord> {
+        let mut records = Vec::new();
+
+        for file in self.files.iter().filter(|f| f.exists()) {
+            // zsh stores non-ASCII bytes in its own encoding; lossy is enough for paths.
+            let Ok(bytes) = fs::read(file) else { continue };
+            let commands = parse_history(&String::from_utf8_lossy(&bytes));
+            if debug {
+                eprintln!("[DEBUG] {} timestamped commands in {}", commands.len(), file.display());
+            }
+
+            let mut cwd = WorkingDir::new(dirs::home_dir(), self.gap);
+            for command in &commands {
+                let project = command_project(command, &mut cwd, projects);
+                if let Some((project, path)) = project
+                    && scope.includes(command.timestamp)
+                {
+                    records.push(TimestampRecord {
+                        path: Some(path),
+                        ..TimestampRecord::new(command.timestamp, project, EventKind::Other)
+                    });
+                }
+            }
+        }
+
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_history_formats() {
+        let zsh = ": 1768932000:0;cd ~/work/app\n: 1768932060:3;cargo test\n";
+        let bash = "#1768932000\nls -la\nno timestamp\n";
+        let fish = "- cmd: vim src/main.rs\n  when: 1768932000\n  paths:\n    - src/main.rs\n- cmd: exit\n  when: 1768932120\n";
+
+        let lines = |content| parse_history(content).into_iter().map(|c| c.line).collect::<Vec<_>>();
+        assert_eq!(lines(zsh), ["cd ~/work/app", "cargo test"]);
+        assert_eq!(lines(bash), ["ls -la"]);
+        assert_eq!(lines(fish), ["vim src/main.rs", "exit"]);
+        assert_eq!(parse_history(fish)[0].paths, ["src/main.rs"]);
+    }
+
+    #[test]
+    fn test_command_projects() {
+        let config: crate::config::ProjectsConfig = serde_json::from_str(r#"{
+            "tracked_path": "",
+            "roots": ["/srv/work"],
+            "git_identity": "off"
+        }"#).unwrap();
+        let projects = ProjectResolver::from_config(&config).unwrap();
+        let mut cwd = WorkingDir::new(Some(PathBuf::from("/home/ola")), Duration::minutes(30));
+        let mut minute = 0;
+        let mut project_after = |pause: i64, line: &str| {
+            minute += pause;
+            let timestamp = NaiveDateTime::default() + Duration::minutes(minute);
+            let command = Command { timestamp, line: line.to_string(), paths: vec![] };
+            command_project(&command, &mut cwd, &projects).map(|(key, _)| projects.display(&key))
+        };
+        let mut project = |line: &str| project_after(1, line);
+
+        assert_eq!(project("ls"), None);
+        assert_eq!(project("vim /srv/work/api/src/lib.rs"), Some("api".to_string()));
+        assert_eq!(project("cd /srv/work/web && npm test"), Some("web".to_string()));
+        assert_eq!(project("git status"), Some("web".to_string()));
+        assert_eq!(project("cd ../api/src"), Some("api".to_string()));
+        assert_eq!(project("cd"), None);
+
+        // After a long pause the `cd` above no longer says where we are.
+        assert_eq!(project("cd /srv/work/web"), Some("web".to_string()));
+        assert_eq!(project_after(45, "git status"), None);
+    }
+}
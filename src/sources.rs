@@ -0,0 +1,149 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::config::{Config, SourceSettings};
+use crate::assistants::{AiderSource, CodexSource, GeminiSource};
+use crate::git::GitSource;
+use crate::jsonl::{ClaudeSource, TimestampRecord};
+use crate::projects::ProjectResolver;
+use crate::shell::ShellSource;
+use crate::timezone::TimeZones;
+
+/// Which part of a source's history to read. A source that cannot filter
+/// cheaply may return more; sessions are cut from whatever it returns.
+#[derive(Debug, Clone, Copy)]
+pub enum Scope {
+    /// Records at or after a UTC instant.
+    Since(NaiveDateTime),
+    All,
+}
+
+impl Scope {
+    /// Records from local midnight of `date` on, in the schedule's zone.
+    pub fn since_date(date: NaiveDate, zones: &TimeZones) -> Self {
+        Scope::Since(zones.to_utc(date.and_time(NaiveTime::MIN)))
+    }
+
+    /// Whether a record (or file modification) at `utc` falls in the scope,
+    /// for sources that read a whole history at once.
+    pub fn includes(&self, utc: NaiveDateTime) -> bool {
+        match self {
+            Scope::Since(start) => utc >= *start,
+            Scope::All => true,
+        }
+    }
+}
+
+/// Something that leaves timestamped traces of work: Claude Code logs, git
+/// commits, shell history. Records go through the same sessionization and
+/// overtime calculation whatever their source.
+pub trait ActivitySource {
+    /// Key of the source in the `sources` config.
+    fn name(&self) -> &'static str;
+
+    /// Project-tagged records, in any order.
+    fn records(&self, scope: Scope, projects: &ProjectResolver, debug: bool) -> Vec<TimestampRecord>;
+}
+
+/// Name of a source as shown in reports.
+pub fn source_label(name: &str) -> &str {
+    match name {
+        "claude" => "Claude Code",
+        "codex" => "Codex CLI",
+        "aider" => "Aider",
+        "gemini" => "Gemini CLI",
+        "shell" => "powłoka",
+        other => other,
+    }
+}
+
+/// The sources enabled in config, with their weights.
+pub struct Sources {
+    enabled: Vec<(Box<dyn ActivitySource>, f64)>,
+}
+
+impl Sources {
+    /// `gap_minutes` is the effective session gap (the CLI value, when given).
+    pub fn from_config(config: &Config, zones: &TimeZones, gap_minutes: u32) -> Self {
+        let mut enabled: Vec<(Box<dyn ActivitySource>, f64)> = Vec::new();
+        let mut add = |source: Box<dyn ActivitySource>, settings: SourceSettings| {
+            if settings.enabled {
+                enabled.push((source, settings.weight.clamp(0.0, 1.0)));
+            }
+        };
+
+        let sources = &config.sources;
+        add(Box::new(ClaudeSource::from_config(&sources.claude)), sources.claude.settings(true));
+        add(Box::new(CodexSource::from_config(&sources.codex)), sources.codex.settings(false));
+        add(Box::new(AiderSource::from_config(&sources.aider, zones.clone())), sources.aider.settings(false));
+        add(Box::new(GeminiSource::from_config(&sources.gemini)), sources.gemini.settings(false));
+        add(Box::new(GitSource::from_config(&sources.git)), sources.git.settings());
+        add(Box::new(ShellSource::from_config(&sources.shell, gap_minutes)), sources.shell.settings());
+
+        Self { enabled }
+    }
+
+    /// Records of all enabled sources as one time-sorted timeline.
+    pub fn collect(&self, scope: Scope, projects: &ProjectResolver, debug: bool) -> Vec<TimestampRecord> {
+        let mut records = Vec::new();
+
+        for (source, weight) in &self.enabled {
+            let found = source.records(scope, projects, debug);
+            if debug {
+                eprintln!("[DEBUG] Source {}: {} records", source.name(), found.len());
+            }
+            records.extend(found.into_iter().map(|record| TimestampRecord {
+                source: source.name(),
+                weight: *weight,
+                ..record
+            }));
+        }
+
+        records.sort_by_key(|r| r.timestamp);
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonl::{build_sessions, EventKind, SessionRules};
+
+    struct Fixed(&'static str, Vec<i64>);
+
+    impl ActivitySource for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn records(&self, _scope: Scope, _projects: &ProjectResolver, _debug: bool) -> Vec<TimestampRecord> {
+            let base = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap().and_hms_opt(18, 0, 0).unwrap();
+            self.1
+                .iter()
+                .map(|m| TimestampRecord::new(base + chrono::Duration::minutes(*m), self.0.to_string(), EventKind::Other))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_sources_merge_and_weight() {
+        let sources = Sources {
+            enabled: vec![
+                (Box::new(Fixed("late", vec![40])), 1.0),
+                (Box::new(Fixed("half", vec![0, 20])), 0.5),
+            ],
+        };
+        let records = sources.collect(Scope::All, &ProjectResolver::default(), false);
+        assert_eq!(records.iter().map(|r| r.project.as_str()).collect::<Vec<_>>(), ["half", "half", "late"]);
+
+        // Both 20-minute pauses follow a "half" record, so only half of each counts.
+        let sessions = build_sessions(&records, &SessionRules::default(), false);
+        assert_eq!(sessions[0].duration_seconds, 2 * 10 * 60);
+        assert_eq!(sessions[0].stats.sources.keys().copied().collect::<Vec<_>>(), ["half", "late"]);
+    }
+
+    #[test]
+    fn test_disabled_source() {
+        let config: Config = serde_json::from_str(r#"{"sources": {"claude": {"enabled": false}}}"#).unwrap();
+        assert!(Sources::from_config(&config, &TimeZones::fixed(chrono_tz::UTC), 30).enabled.is_empty());
+    }
+}
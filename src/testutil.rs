@@ -0,0 +1,40 @@
+//! Fixtures shared by the unit tests.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A fresh directory under the system temp dir, removed when dropped, so a
+/// failing assertion does not leave it behind.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `name` keeps the directories of tests running in parallel apart.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("after15-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+
+    /// Writes a file, creating its parent directories; returns its path.
+    pub fn write(&self, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
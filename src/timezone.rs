@@ -0,0 +1,159 @@
+use chrono::{Duration, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::config::Config;
+
+/// Time zone used to turn UTC log timestamps into local work time: the
+/// configured (or system) zone, optionally replaced for travel periods.
+#[derive(Debug, Clone)]
+pub struct TimeZones {
+    default: Tz,
+    periods: Vec<(NaiveDate, NaiveDate, Tz)>,
+}
+
+fn parse_zone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>()
+        .map_err(|_| format!("Nieznana strefa czasowa: {} (użyj nazwy IANA, np. Europe/Warsaw)", name))
+}
+
+/// System time zone, or Europe/Warsaw when it cannot be determined.
+pub fn system_zone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse::<Tz>().ok())
+        .unwrap_or(chrono_tz::Europe::Warsaw)
+}
+
+impl TimeZones {
+    pub fn fixed(zone: Tz) -> Self {
+        Self {
+            default: zone,
+            periods: Vec::new(),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let default = match &config.timezone {
+            Some(name) => parse_zone(name)?,
+            None => system_zone(),
+        };
+
+        let mut periods = Vec::new();
+        for period in &config.timezone_periods {
+            if period.from > period.to {
+                return Err(format!("Okres strefy czasowej {}: from > to", period.timezone));
+            }
+            periods.push((period.from, period.to, parse_zone(&period.timezone)?));
+        }
+
+        Ok(Self { default, periods })
+    }
+
+    pub fn zone_for_date(&self, date: NaiveDate) -> Tz {
+        self.periods
+            .iter()
+            .find(|(from, to, _)| date >= *from && date <= *to)
+            .map(|(_, _, zone)| *zone)
+            .unwrap_or(self.default)
+    }
+
+    /// Zone in effect at a UTC instant, picked like `to_utc` does: by the
+    /// local date the zone itself gives. When two zones fit at a switch, the
+    /// later date wins (a day starts at `to_utc` of its midnight); when none
+    /// does (the hour skipped on the way west), the earlier one.
+    pub fn zone_at(&self, utc: NaiveDateTime) -> Tz {
+        let local_date = |zone: &Tz| utc.and_utc().with_timezone(zone).date_naive();
+        let candidates = std::iter::once(self.default).chain(self.periods.iter().map(|(_, _, zone)| *zone));
+        candidates
+            .clone()
+            .filter(|zone| self.zone_for_date(local_date(zone)) == *zone)
+            .max_by_key(local_date)
+            .or_else(|| candidates.min_by_key(local_date))
+            .unwrap_or(self.default)
+    }
+
+    pub fn to_local(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        utc.and_utc().with_timezone(&self.zone_at(utc)).naive_local()
+    }
+
+    /// Instant of a wall-clock time. A repeated (fall-back) time resolves to
+    /// its first occurrence; a skipped (spring-forward) time is read with the
+    /// offset from before the gap, as RFC 5545 does.
+    pub fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        let zone = self.zone_for_date(local.date());
+        match zone.from_local_datetime(&local) {
+            LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => dt.naive_utc(),
+            LocalResult::None => {
+                let before = zone.offset_from_utc_datetime(&(local - Duration::days(1)));
+                local - Duration::seconds(before.fix().local_minus_utc() as i64)
+            }
+        }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.to_local(Utc::now().naive_utc()).date()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_travel_period() {
+        let config: Config = serde_json::from_str(r#"{
+            "timezone": "Europe/Warsaw",
+            "timezone_periods": [{"from": "2026-03-02", "to": "2026-03-06", "timezone": "Europe/Lisbon"}]
+        }"#).unwrap();
+        let zones = TimeZones::from_config(&config).unwrap();
+        let utc = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap().and_hms_opt(17, 0, 0).unwrap();
+
+        assert_eq!(zones.to_local(utc(1)).to_string(), "2026-03-01 18:00:00");
+        assert_eq!(zones.to_local(utc(3)).to_string(), "2026-03-03 17:00:00");
+        assert_eq!(zones.to_utc(zones.to_local(utc(3))), utc(3));
+    }
+
+    #[test]
+    fn test_period_boundaries_round_trip() {
+        let config: Config = serde_json::from_str(r#"{
+            "timezone": "Europe/Warsaw",
+            "timezone_periods": [{"from": "2026-03-02", "to": "2026-03-06", "timezone": "Europe/Lisbon"}]
+        }"#).unwrap();
+        let zones = TimeZones::from_config(&config).unwrap();
+        let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap().and_hms_opt(20, 0, 0).unwrap();
+        // Warsaw's 2 March starts at 23:00 UTC, Lisbon's an hour later: no
+        // wall-clock time maps into that hour.
+        let skipped = start + Duration::hours(3)..start + Duration::hours(4);
+
+        for step in 0..6 * 48 {
+            let utc = start + Duration::minutes(30 * step);
+            if !skipped.contains(&utc) {
+                assert_eq!(zones.to_utc(zones.to_local(utc)), utc, "{}", utc);
+            }
+        }
+        for day in 1..=8 {
+            let midnight = NaiveDate::from_ymd_opt(2026, 3, day).unwrap().and_hms_opt(0, 0, 0).unwrap();
+            assert_eq!(zones.to_local(zones.to_utc(midnight)), midnight);
+        }
+        // Lisbon's last evening ends at Warsaw's midnight, 23:00 UTC.
+        let late = NaiveDate::from_ymd_opt(2026, 3, 6).unwrap().and_hms_opt(23, 30, 0).unwrap();
+        assert_eq!(zones.to_local(late).to_string(), "2026-03-07 00:30:00");
+    }
+
+    #[test]
+    fn test_dst_transitions() {
+        let zones = TimeZones::fixed(chrono_tz::Europe::Warsaw);
+        let at = |m, d, h, min| NaiveDate::from_ymd_opt(2026, m, d).unwrap().and_hms_opt(h, min, 0).unwrap();
+
+        // 02:30 does not exist on 2026-03-29; it is read as CET, i.e. 03:30 CEST.
+        assert_eq!(zones.to_utc(at(3, 29, 2, 30)), at(3, 29, 1, 30));
+        // 02:30 happens twice on 2026-10-25; the first one (CEST) wins.
+        assert_eq!(zones.to_utc(at(10, 25, 2, 30)), at(10, 25, 0, 30));
+    }
+
+    #[test]
+    fn test_invalid_zone() {
+        let config: Config = serde_json::from_str(r#"{"timezone": "Europe/Atlantis"}"#).unwrap();
+        assert!(TimeZones::from_config(&config).is_err());
+    }
+}